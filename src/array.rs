@@ -0,0 +1,886 @@
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+
+use crate::{
+    environment::{drop_values, NativeFn, NativeFunction, Value},
+    error::JustError,
+    interpreter::Interpreter,
+    object::{array_index, Object},
+    operations::{to_boolean, to_number, to_string, to_uint32},
+};
+
+/// The longest array this interpreter stores. JavaScript allows lengths up
+/// to 2^32 - 1, but elements are stored densely, so `a[4294967294] = 1`
+/// would otherwise try to allocate hundreds of gigabytes.
+const MAX_LENGTH: usize = 1 << 24;
+
+/// An array's own state: its elements, with `None` for a hole like the
+/// middle of `[1, , 3]`, and the properties that are not indices, like
+/// `a.foo`.
+#[derive(Debug, Default)]
+pub struct ArrayObject {
+    pub elements: Vec<Option<Value>>,
+    pub properties: Object,
+}
+
+pub type Array = Rc<RefCell<ArrayObject>>;
+
+impl ArrayObject {
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// The element at `index`, or `None` for a hole or past the end.
+    pub fn get(&self, index: usize) -> Option<Value> {
+        self.elements.get(index).cloned().flatten()
+    }
+
+    /// The elements in order, with holes read as `undefined`, as iterating
+    /// the array sees them.
+    pub fn values(&self) -> impl Iterator<Item = Value> + '_ {
+        self.elements
+            .iter()
+            .map(|element| element.clone().unwrap_or(Value::Undefined))
+    }
+
+    /// Writes the element at `index`, leaving holes between the old end
+    /// and `index`.
+    fn set(&mut self, index: usize, value: Value) -> Result<(), JustError> {
+        if index >= self.len() {
+            self.set_length(index + 1)?;
+        }
+
+        self.elements[index] = Some(value);
+        Ok(())
+    }
+
+    /// Empties the array, handing back its elements and properties.
+    pub fn take_values(&mut self) -> Vec<Value> {
+        let mut values: Vec<_> = self.elements.drain(..).flatten().collect();
+        values.extend(self.properties.take_values());
+        values
+    }
+
+    /// Truncates the array, or extends it with holes.
+    fn set_length(&mut self, len: usize) -> Result<(), JustError> {
+        if len > MAX_LENGTH {
+            return Err(JustError::range(format!(
+                "Array length {} exceeds the supported maximum of {}",
+                len, MAX_LENGTH
+            )));
+        }
+
+        self.elements.resize(len, None);
+        Ok(())
+    }
+}
+
+impl Drop for ArrayObject {
+    fn drop(&mut self) {
+        drop_values(self.take_values());
+    }
+}
+
+/// Looks up the `Array.prototype` method called `name`.
+pub fn method(name: &str) -> Option<NativeFunction> {
+    let (name, function): (&str, NativeFn) = match name {
+        "push" => ("push", push),
+        "pop" => ("pop", pop),
+        "shift" => ("shift", shift),
+        "unshift" => ("unshift", unshift),
+        "slice" => ("slice", slice),
+        "splice" => ("splice", splice),
+        "concat" => ("concat", concat),
+        "indexOf" => ("indexOf", index_of),
+        "includes" => ("includes", includes),
+        "join" => ("join", join),
+        "toString" => ("toString", array_to_string),
+        "reverse" => ("reverse", reverse),
+        "sort" => ("sort", sort),
+        "map" => ("map", map),
+        "filter" => ("filter", filter),
+        "reduce" => ("reduce", reduce),
+        "forEach" => ("forEach", for_each),
+        "find" => ("find", find),
+        "some" => ("some", some),
+        "every" => ("every", every),
+        "flat" => ("flat", flat),
+        "flatMap" => ("flatMap", flat_map),
+        _ => return None,
+    };
+
+    Some(NativeFunction { name, function })
+}
+
+pub fn new_array(elements: Vec<Value>) -> Value {
+    new_array_with_holes(elements.into_iter().map(Some).collect())
+}
+
+pub fn new_array_with_holes(elements: Vec<Option<Value>>) -> Value {
+    Value::Array(Rc::new(RefCell::new(ArrayObject {
+        elements,
+        properties: Object::new(),
+    })))
+}
+
+/// Reads `array[key]`: an element, the length, a property of the array's
+/// own or an `Array.prototype` method.
+pub fn get_property(array: &Array, key: &str) -> Value {
+    let array = array.borrow();
+    if key == "length" {
+        return Value::Number(array.len() as f64);
+    }
+
+    if let Some(index) = array_index(key) {
+        return array.get(index as usize).unwrap_or(Value::Undefined);
+    }
+
+    match array.properties.get(key) {
+        Some(value) => value,
+        None => method(key).map_or(Value::Undefined, Value::NativeFunction),
+    }
+}
+
+/// Whether `array[key]` finds anything, as the `in` operator asks. Holes
+/// are missing elements.
+pub fn has_property(array: &Array, key: &str) -> bool {
+    let array = array.borrow();
+    match array_index(key) {
+        Some(index) => array.get(index as usize).is_some(),
+        None => key == "length" || array.properties.lookup(key).is_some() || method(key).is_some(),
+    }
+}
+
+/// `delete array[key]`, which leaves a hole where an element was. The
+/// length cannot be deleted, which makes the result `false`.
+pub fn delete_property(array: &Array, key: &str) -> bool {
+    let mut array = array.borrow_mut();
+    match array_index(key) {
+        Some(index) => {
+            if let Some(element) = array.elements.get_mut(index as usize) {
+                *element = None;
+            }
+            true
+        }
+        None if key == "length" => false,
+        None => {
+            array.properties.delete(key);
+            true
+        }
+    }
+}
+
+/// Writes `array[key] = value`. Setting `length` converts the value to a
+/// number first, which has to be a valid length.
+pub fn set_property(
+    interpreter: &mut Interpreter,
+    array: &Array,
+    key: String,
+    value: Value,
+) -> Result<(), JustError> {
+    if key == "length" {
+        let n = to_number(interpreter, &value)?;
+        if to_uint32(n) as f64 != n {
+            return Err(JustError::range("Invalid array length"));
+        }
+        return array.borrow_mut().set_length(n as usize);
+    }
+
+    let mut array = array.borrow_mut();
+    match array_index(&key) {
+        Some(index) => array.set(index as usize, value),
+        None => {
+            array.properties.set(key, value);
+            Ok(())
+        }
+    }
+}
+
+/// The keys `for...in` and object spread visit: the indices of the
+/// elements that are not holes, then the other properties.
+pub fn keys(array: &Array) -> Vec<String> {
+    let array = array.borrow();
+    let indices = array
+        .elements
+        .iter()
+        .enumerate()
+        .filter(|(_, element)| element.is_some())
+        .map(|(index, _)| index.to_string());
+
+    indices.chain(array.properties.keys()).collect()
+}
+
+fn this_array(this: &Value, method: &str) -> Result<Array, JustError> {
+    match this {
+        Value::Array(array) => Ok(Rc::clone(array)),
+        _ => Err(JustError::type_error(format!(
+            "Array.prototype.{} called on a non-array",
+            method
+        ))),
+    }
+}
+
+fn argument(arguments: &[Value], index: usize) -> Value {
+    arguments.get(index).cloned().unwrap_or(Value::Undefined)
+}
+
+/// ToIntegerOrInfinity: `value` converted to a number and truncated, with
+/// NaN becoming 0.
+fn to_integer(interpreter: &mut Interpreter, value: &Value) -> Result<f64, JustError> {
+    let n = to_number(interpreter, value)?;
+    Ok(if n.is_nan() { 0.0 } else { n.trunc() })
+}
+
+/// Resolves a possibly negative `start`/`end` style argument against `len`
+/// (ToIntegerOrInfinity followed by clamping), or `default` when it is
+/// missing or `undefined`.
+fn relative_index(
+    interpreter: &mut Interpreter,
+    value: Option<&Value>,
+    len: usize,
+    default: usize,
+) -> Result<usize, JustError> {
+    let n = match value {
+        Some(Value::Undefined) | None => return Ok(default),
+        Some(value) => to_integer(interpreter, value)?,
+    };
+
+    Ok(if n < 0.0 {
+        (len as f64 + n).max(0.0) as usize
+    } else {
+        n.min(len as f64) as usize
+    })
+}
+
+fn callback(arguments: &[Value], method: &str) -> Result<Value, JustError> {
+    match arguments.first() {
+        Some(function @ (Value::Function(_) | Value::NativeFunction(_))) => Ok(function.clone()),
+        Some(value) => Err(JustError::type_error(format!(
+            "{} is not a function (in Array.prototype.{})",
+            value, method
+        ))),
+        None => Err(JustError::type_error(format!(
+            "undefined is not a function (in Array.prototype.{})",
+            method
+        ))),
+    }
+}
+
+/// Calls `callback(element, index, array)` for every element, skipping
+/// holes, and stops early when `visit` returns `false`. `visit` gets the
+/// index, the element and the callback's result. The length is read once
+/// up front and elements are re-read on every step, so callbacks may
+/// mutate the array.
+fn each(
+    interpreter: &mut Interpreter,
+    array: &Array,
+    callback: &Value,
+    mut visit: impl FnMut(usize, Value, Value) -> bool,
+) -> Result<(), JustError> {
+    let len = array.borrow().len();
+
+    for index in 0..len {
+        let element = match array.borrow().get(index) {
+            Some(element) => element,
+            None => continue,
+        };
+
+        let result = interpreter.call_value(
+            callback,
+            Value::Undefined,
+            vec![
+                element.clone(),
+                Value::Number(index as f64),
+                Value::Array(Rc::clone(array)),
+            ],
+        )?;
+
+        if !visit(index, element, result) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn push(_: &mut Interpreter, this: Value, arguments: Vec<Value>) -> Result<Value, JustError> {
+    let array = this_array(&this, "push")?;
+    let mut array = array.borrow_mut();
+    array.elements.extend(arguments.into_iter().map(Some));
+
+    Ok(Value::Number(array.len() as f64))
+}
+
+fn pop(_: &mut Interpreter, this: Value, _: Vec<Value>) -> Result<Value, JustError> {
+    let array = this_array(&this, "pop")?;
+    let popped = array.borrow_mut().elements.pop().flatten();
+
+    Ok(popped.unwrap_or(Value::Undefined))
+}
+
+fn shift(_: &mut Interpreter, this: Value, _: Vec<Value>) -> Result<Value, JustError> {
+    let array = this_array(&this, "shift")?;
+    let mut array = array.borrow_mut();
+
+    if array.elements.is_empty() {
+        Ok(Value::Undefined)
+    } else {
+        Ok(array.elements.remove(0).unwrap_or(Value::Undefined))
+    }
+}
+
+fn unshift(_: &mut Interpreter, this: Value, arguments: Vec<Value>) -> Result<Value, JustError> {
+    let array = this_array(&this, "unshift")?;
+    let mut array = array.borrow_mut();
+    array.elements.splice(0..0, arguments.into_iter().map(Some));
+
+    Ok(Value::Number(array.len() as f64))
+}
+
+fn slice(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "slice")?;
+    let len = array.borrow().len();
+    let start = relative_index(interpreter, arguments.first(), len, 0)?;
+    let end = relative_index(interpreter, arguments.get(1), len, len)?;
+    let array = array.borrow();
+
+    Ok(new_array_with_holes(if start < end {
+        array.elements[start..end].to_vec()
+    } else {
+        Vec::new()
+    }))
+}
+
+fn splice(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "splice")?;
+    let len = array.borrow().len();
+    let start = relative_index(interpreter, arguments.first(), len, 0)?;
+    let delete_count = match arguments.get(1) {
+        None if arguments.is_empty() => 0,
+        None => len - start,
+        Some(count) => to_integer(interpreter, count)?.clamp(0.0, (len - start) as f64) as usize,
+    };
+    let items = arguments.into_iter().skip(2).map(Some);
+    let mut array = array.borrow_mut();
+
+    let removed = array
+        .elements
+        .splice(start..start + delete_count, items)
+        .collect();
+    Ok(new_array_with_holes(removed))
+}
+
+fn concat(_: &mut Interpreter, this: Value, arguments: Vec<Value>) -> Result<Value, JustError> {
+    let array = this_array(&this, "concat")?;
+    let mut result = array.borrow().elements.clone();
+
+    for argument in arguments {
+        match argument {
+            Value::Array(other) => result.extend(other.borrow().elements.iter().cloned()),
+            value => result.push(Some(value)),
+        }
+    }
+
+    Ok(new_array_with_holes(result))
+}
+
+fn index_of(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "indexOf")?;
+    let target = argument(&arguments, 0);
+    let start = relative_index(interpreter, arguments.get(1), array.borrow().len(), 0)?;
+    let array = array.borrow();
+
+    let index = array
+        .elements
+        .iter()
+        .enumerate()
+        .skip(start)
+        .find(|(_, element)| element.as_ref().is_some_and(|e| e.strict_equals(&target)))
+        .map_or(-1.0, |(index, _)| index as f64);
+
+    Ok(Value::Number(index))
+}
+
+fn includes(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "includes")?;
+    let target = argument(&arguments, 0);
+    let start = relative_index(interpreter, arguments.get(1), array.borrow().len(), 0)?;
+    let array = array.borrow();
+
+    // Unlike `indexOf`, holes read as `undefined` here.
+    let found = array
+        .values()
+        .skip(start)
+        .any(|element| element.same_value_zero(&target));
+
+    Ok(Value::Boolean(found))
+}
+
+fn join(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "join")?;
+    let separator = match arguments.first() {
+        Some(Value::Undefined) | None => ",".to_string(),
+        Some(separator) => to_string(interpreter, separator)?,
+    };
+
+    if interpreter
+        .joining
+        .iter()
+        .any(|joining| Rc::ptr_eq(joining, &array))
+    {
+        return Ok(Value::String(String::new()));
+    }
+
+    // Elements are converted from a snapshot, since `toString` methods may
+    // change the array.
+    let elements: Vec<_> = array.borrow().values().collect();
+    interpreter.joining.push(array);
+    let parts = elements
+        .iter()
+        .map(|element| match element {
+            Value::Null | Value::Undefined => Ok(String::new()),
+            element => to_string(interpreter, element),
+        })
+        .collect::<Result<Vec<_>, _>>();
+    interpreter.joining.pop();
+
+    Ok(Value::String(parts?.join(&separator)))
+}
+
+fn array_to_string(
+    interpreter: &mut Interpreter,
+    this: Value,
+    _: Vec<Value>,
+) -> Result<Value, JustError> {
+    this_array(&this, "toString")?;
+    join(interpreter, this, Vec::new())
+}
+
+fn reverse(_: &mut Interpreter, this: Value, _: Vec<Value>) -> Result<Value, JustError> {
+    let array = this_array(&this, "reverse")?;
+    array.borrow_mut().elements.reverse();
+
+    Ok(this)
+}
+
+fn sort(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "sort")?;
+    let comparator = match arguments.first() {
+        None | Some(Value::Undefined) => None,
+        Some(_) => Some(callback(&arguments, "sort")?),
+    };
+
+    // `undefined` elements go to the end without ever being compared, and
+    // holes after them.
+    let (elements, undefined): (Vec<_>, Vec<_>) = array
+        .borrow()
+        .elements
+        .iter()
+        .flatten()
+        .cloned()
+        .partition(|element| !matches!(element, Value::Undefined));
+    let holes = array.borrow().len() - elements.len() - undefined.len();
+    let mut sorted = merge_sort(elements, &mut |a, b| match &comparator {
+        Some(comparator) => {
+            let result =
+                interpreter.call_value(comparator, Value::Undefined, vec![a.clone(), b.clone()])?;
+            let order = to_number(interpreter, &result)?;
+            Ok(if order < 0.0 {
+                Ordering::Less
+            } else if order > 0.0 {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            })
+        }
+        // Without a comparator elements are compared as strings, by UTF-16
+        // code units.
+        None => {
+            let a = to_string(interpreter, a)?;
+            let b = to_string(interpreter, b)?;
+            Ok(a.encode_utf16().cmp(b.encode_utf16()))
+        }
+    })?;
+    sorted.extend(undefined);
+
+    let mut sorted: Vec<_> = sorted.into_iter().map(Some).collect();
+    sorted.resize(sorted.len() + holes, None);
+    array.borrow_mut().elements = sorted;
+    Ok(this)
+}
+
+/// A stable merge sort with a fallible comparator. The standard library
+/// sorts may panic when a user comparator is inconsistent, so they are not
+/// used here.
+fn merge_sort(
+    mut elements: Vec<Value>,
+    compare: &mut impl FnMut(&Value, &Value) -> Result<Ordering, JustError>,
+) -> Result<Vec<Value>, JustError> {
+    if elements.len() <= 1 {
+        return Ok(elements);
+    }
+
+    let right = elements.split_off(elements.len() / 2);
+    let left = merge_sort(elements, compare)?;
+    let right = merge_sort(right, compare)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        if compare(a, b)? == Ordering::Greater {
+            merged.extend(right.next());
+        } else {
+            merged.extend(left.next());
+        }
+    }
+    merged.extend(left);
+    merged.extend(right);
+
+    Ok(merged)
+}
+
+fn map(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "map")?;
+    let callback = callback(&arguments, "map")?;
+    // The result keeps the array's holes.
+    let mut mapped = vec![None; array.borrow().len()];
+
+    each(interpreter, &array, &callback, |index, _, result| {
+        mapped[index] = Some(result);
+        true
+    })?;
+
+    Ok(new_array_with_holes(mapped))
+}
+
+fn filter(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "filter")?;
+    let callback = callback(&arguments, "filter")?;
+    let mut kept = Vec::new();
+
+    each(interpreter, &array, &callback, |_, element, result| {
+        if to_boolean(&result) {
+            kept.push(element);
+        }
+        true
+    })?;
+
+    Ok(new_array(kept))
+}
+
+fn reduce(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "reduce")?;
+    let callback = callback(&arguments, "reduce")?;
+    let len = array.borrow().len();
+
+    // Without an initial value the first element that is not a hole is.
+    let first = || (0..len).find_map(|index| Some((array.borrow().get(index)?, index + 1)));
+    let (mut accumulator, start) = match arguments.get(1) {
+        Some(initial) => (initial.clone(), 0),
+        None => match first() {
+            Some(first) => first,
+            None => {
+                return Err(JustError::type_error(
+                    "Reduce of empty array with no initial value",
+                ))
+            }
+        },
+    };
+
+    for index in start..len {
+        let element = match array.borrow().get(index) {
+            Some(element) => element,
+            None => continue,
+        };
+
+        accumulator = interpreter.call_value(
+            &callback,
+            Value::Undefined,
+            vec![
+                accumulator,
+                element,
+                Value::Number(index as f64),
+                Value::Array(Rc::clone(&array)),
+            ],
+        )?;
+    }
+
+    Ok(accumulator)
+}
+
+fn for_each(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "forEach")?;
+    let callback = callback(&arguments, "forEach")?;
+
+    each(interpreter, &array, &callback, |_, _, _| true)?;
+
+    Ok(Value::Undefined)
+}
+
+fn find(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "find")?;
+    let callback = callback(&arguments, "find")?;
+    let len = array.borrow().len();
+
+    // Unlike the other callback methods, `find` visits holes too, as
+    // `undefined`.
+    for index in 0..len {
+        let element = array.borrow().get(index).unwrap_or(Value::Undefined);
+        let result = interpreter.call_value(
+            &callback,
+            Value::Undefined,
+            vec![
+                element.clone(),
+                Value::Number(index as f64),
+                Value::Array(Rc::clone(&array)),
+            ],
+        )?;
+
+        if to_boolean(&result) {
+            return Ok(element);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+fn some(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "some")?;
+    let callback = callback(&arguments, "some")?;
+    let mut any = false;
+
+    each(interpreter, &array, &callback, |_, _, result| {
+        any = to_boolean(&result);
+        !any
+    })?;
+
+    Ok(Value::Boolean(any))
+}
+
+fn every(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "every")?;
+    let callback = callback(&arguments, "every")?;
+    let mut all = true;
+
+    each(interpreter, &array, &callback, |_, _, result| {
+        all = to_boolean(&result);
+        all
+    })?;
+
+    Ok(Value::Boolean(all))
+}
+
+fn flat(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "flat")?;
+    let depth = match arguments.first() {
+        Some(Value::Undefined) | None => 1.0,
+        Some(depth) => to_integer(interpreter, depth)?,
+    };
+
+    let mut flattened = Vec::new();
+    flatten_into(interpreter, &mut flattened, &array.borrow().elements, depth)?;
+
+    Ok(new_array(flattened))
+}
+
+/// Appends `elements` to `target`, dropping holes and flattening nested
+/// arrays `depth` levels deep.
+fn flatten_into(
+    interpreter: &Interpreter,
+    target: &mut Vec<Value>,
+    elements: &[Option<Value>],
+    depth: f64,
+) -> Result<(), JustError> {
+    interpreter.stack.check()?;
+
+    for element in elements.iter().flatten() {
+        match element {
+            Value::Array(inner) if depth >= 1.0 => {
+                flatten_into(interpreter, target, &inner.borrow().elements, depth - 1.0)?
+            }
+            element => target.push(element.clone()),
+        }
+    }
+
+    Ok(())
+}
+
+fn flat_map(
+    interpreter: &mut Interpreter,
+    this: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let array = this_array(&this, "flatMap")?;
+    let callback = callback(&arguments, "flatMap")?;
+    let mut flattened = Vec::new();
+
+    each(interpreter, &array, &callback, |_, _, result| {
+        match result {
+            Value::Array(inner) => {
+                flattened.extend(inner.borrow().elements.iter().flatten().cloned())
+            }
+            result => flattened.push(result),
+        }
+        true
+    })?;
+
+    Ok(new_array(flattened))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{assert_bool, assert_error, assert_number, assert_string};
+
+    #[test]
+    fn includes_and_index_of_compare_differently() {
+        assert_bool("[0 / 0].includes(0 / 0)", true);
+        assert_bool("[-0].includes(0)", true);
+        assert_number("[0 / 0].indexOf(0 / 0)", -1.0);
+        assert_number("[-0].indexOf(0)", 0.0);
+    }
+
+    #[test]
+    fn holes_are_skipped_by_callbacks_but_read_as_undefined() {
+        let numbers = [
+            ("let n = 0; [1, , 3].forEach(() => n++); n", 2.0),
+            ("[1, , 3].filter(() => true).length", 2.0),
+            ("[1, , 3].reduce((a, b) => a + b)", 4.0),
+            ("[, , 1].reduce((a, b) => a + b)", 1.0),
+            ("[1, , 3].indexOf(undefined)", -1.0),
+            ("[1, , 3].map((x) => x * 2).length", 3.0),
+            ("[...[1, , 3]].length", 3.0),
+            ("let a = []; a[5] = 1; a.length", 6.0),
+        ];
+        for (source, expected) in numbers {
+            assert_number(source, expected);
+        }
+
+        let booleans = [
+            ("[1, , 3].includes(undefined)", true),
+            ("[1, , 3][1] === undefined", true),
+            ("[1, , 3].find((x) => x === undefined) === undefined", true),
+        ];
+        for (source, expected) in booleans {
+            assert_bool(source, expected);
+        }
+
+        assert_string("[1, , 3].join()", "1,,3");
+        assert_string(
+            "let keys = []; for (const k in [1, , 3]) keys.push(k); keys.join()",
+            "0,2",
+        );
+    }
+
+    #[test]
+    fn arrays_keep_properties_that_are_not_indices() {
+        assert_string(r#"let a = [1]; a.foo = "x"; a.foo"#, "x");
+        assert_number("let a = [1]; a[-1] = 5; a[-1] + a.length", 6.0);
+        assert_string(
+            r#"let a = [1]; a.foo = 2; let keys = []; for (const k in a) keys.push(k); keys.join()"#,
+            "0,foo",
+        );
+    }
+
+    #[test]
+    fn length_is_converted_and_checked() {
+        assert_number(r#"let a = [1, 2, 3]; a.length = "1"; a.length"#, 1.0);
+        assert_number(
+            "let a = [1, 2]; a.length = { valueOf: () => 1 }; a.length",
+            1.0,
+        );
+        assert_error(
+            "let a = []; a.length = -1",
+            "RangeError: Invalid array length",
+        );
+        assert_error(
+            "let a = []; a.length = 1.5",
+            "RangeError: Invalid array length",
+        );
+        assert_error(
+            "let a = []; a[4294967294] = 1",
+            "RangeError: Array length 4294967295 exceeds the supported maximum of 16777216",
+        );
+        assert_error(
+            "let a = []; a.length = 4294967295",
+            "RangeError: Array length 4294967295 exceeds the supported maximum of 16777216",
+        );
+    }
+
+    #[test]
+    fn relative_indices_are_converted_to_integers() {
+        let cases = [
+            ("[1, 2, 3].slice(0, undefined)", "1,2,3"),
+            (r#"[1, 2, 3].slice("1")"#, "2,3"),
+            ("[1, 2, 3].slice(true)", "2,3"),
+            (r#"[1, 2, 3].slice(1, "-1")"#, "2"),
+            ("[1, 2, 3].slice(0 / 0)", "1,2,3"),
+            (r#"let a = [1, 2, 3]; a.splice("1"); a"#, "1"),
+            (r#"let a = [1, 2, 3]; a.splice(0, "2"); a"#, "3"),
+            ("let a = [1, 2, 3]; a.splice(1, undefined); a", "1,2,3"),
+            (r#"[[1, [2, [3]]]].flat("2")"#, "1,2,3"),
+        ];
+        for (source, expected) in cases {
+            assert_string(&format!("{}.join()", source), expected);
+        }
+
+        assert_number(r#"[1, 2, 1].indexOf(1, "1")"#, 2.0);
+        assert_bool(r#"[1, 2].includes(1, "1")"#, false);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::lexer::TokenType;
+use crate::{error::Span, lexer::TokenType};
 
 #[derive(Debug, Clone)]
 pub enum Expression {
@@ -11,37 +11,98 @@ pub enum Expression {
         left: Box<Expression>,
         operator: TokenType,
         right: Box<Expression>,
+        span: Span, // The operator
+    },
+    Logical {
+        left: Box<Expression>,
+        operator: TokenType,    // `&&`, `||` or `??`
+        right: Box<Expression>, // Only evaluated if `left` does not decide the result
+    },
+    Conditional {
+        condition: Box<Expression>,
+        then_branch: Box<Expression>,
+        else_branch: Box<Expression>,
     },
     Grouping(Box<Expression>),
-    Variable(String), // Represents variable usage
-    Assignment {
+    Sequence(Vec<Expression>), // `a, b, c`, which evaluates to `c`
+    Variable {
         name: String,
+        span: Span, // The identifier
+    },
+    Assignment {
+        target: Box<Pattern>,
+        operator: TokenType, // `=`, or a compound one like `+=` or `&&=`
         value: Box<Expression>,
+        span: Span, // The assignment target
+    },
+    Update {
+        target: Box<Pattern>, // An identifier or member
+        operator: TokenType,  // `++` or `--`
+        prefix: bool,         // true for `++x`, false for `x++`
+        span: Span,           // The target
     },
     Call {
         callee: Box<Expression>,
         arguments: Vec<Expression>,
+        span: Span, // From the callee up to the closing `)`
+    },
+    New {
+        callee: Box<Expression>,
+        arguments: Vec<Expression>, // Empty for `new F`
+        span: Span,                 // From `new` up to the end of the arguments
+    },
+    This(Span),
+    PrivateName(String), // `#name`, only as the property of a `Member`
+    Class(Box<Class>),
+    Function {
+        name: Option<String>,
+        parameters: Vec<Parameter>,
+        body: Vec<Statement>,
+    },
+    Arrow {
+        parameters: Vec<Parameter>,
+        body: Vec<Statement>, // A concise body `x => x` becomes `return x;`
+    },
+    SuperCall {
+        arguments: Vec<Expression>,
+        span: Span, // From `super` up to the closing `)`
+    },
+    SuperMember {
+        property: Box<Expression>,
+        computed: bool,
+        span: Span, // The property
     },
     Member {
         object: Box<Expression>,
-        property: String,
-        computed: bool, // true for `arr[0]`, false for `obj.key`
+        property: Box<Expression>, // A string literal for `obj.key`
+        computed: bool,            // true for `arr[0]`, false for `obj.key`
+        span: Span,                // The property
+    },
+    ArrayLiteral {
+        elements: Vec<Option<Expression>>, // `None` for a hole, e.g. `[1, , 3]`
+        span: Span,                        // From `[` up to `]`
+    },
+    ObjectLiteral {
+        properties: Vec<PropertyDefinition>,
+        span: Span, // From `{` up to `}`
+    },
+    // `...xs` in arguments and array literals
+    Spread {
+        argument: Box<Expression>,
+        span: Span, // From `...` up to the end of the argument
     },
-    ArrayLiteral(Vec<Expression>),
-    ObjectLiteral(Vec<(String, Expression)>),
 }
 
 #[derive(Debug, Clone)]
 pub enum Statement {
     Expression(Expression), // An expression used as a statement
     VariableDeclaration {
-        name: String,
-        initializer: Option<Expression>,
-        can_reassign: bool,
+        kind: DeclarationKind,
+        declarators: Vec<Declarator>, // `a = 1` and `b` in `let a = 1, b;`
     },
     FunctionDeclaration {
         name: String,
-        parameters: Vec<String>,
+        parameters: Vec<Parameter>,
         body: Vec<Statement>,
     },
     Block(Vec<Statement>), // Represents `{ ... }`
@@ -54,7 +115,216 @@ pub enum Statement {
         condition: Expression,
         body: Box<Statement>,
     },
+    For {
+        initializer: Option<Box<Statement>>,
+        condition: Option<Expression>,
+        update: Option<Expression>,
+        body: Box<Statement>,
+    },
+    ForIn {
+        target: ForTarget,
+        object: Expression,
+        body: Box<Statement>,
+    },
+    ForOf {
+        target: ForTarget,
+        iterable: Expression,
+        body: Box<Statement>,
+    },
+    Labeled {
+        label: String,
+        body: Box<Statement>,
+    },
+    Break(Option<String>),      // Supports `break;` and `break label;`
+    Continue(Option<String>),   // Supports `continue;` and `continue label;`
     Return(Option<Expression>), // Supports `return;` and `return expr;`
+    Class(Class),               // A class declaration, which always has a name
+}
+
+impl Statement {
+    /// The names the statement declares with `var`, including those in
+    /// nested blocks and loop heads but not in nested functions, which all
+    /// belong to the enclosing function or script.
+    pub fn var_declared_names(&self) -> Vec<String> {
+        match self {
+            Statement::VariableDeclaration {
+                kind: DeclarationKind::Var,
+                declarators,
+            } => declarators
+                .iter()
+                .flat_map(|declarator| declarator.pattern.bound_names())
+                .collect(),
+            Statement::Block(statements) => statements
+                .iter()
+                .flat_map(Statement::var_declared_names)
+                .collect(),
+            Statement::If {
+                then_branch,
+                else_branch,
+                ..
+            } => std::iter::once(then_branch)
+                .chain(else_branch)
+                .flat_map(|branch| branch.var_declared_names())
+                .collect(),
+            Statement::While { body, .. } | Statement::Labeled { body, .. } => {
+                body.var_declared_names()
+            }
+            Statement::For {
+                initializer, body, ..
+            } => initializer
+                .iter()
+                .chain(Some(body))
+                .flat_map(|statement| statement.var_declared_names())
+                .collect(),
+            Statement::ForIn { target, body, .. } | Statement::ForOf { target, body, .. } => {
+                let mut names = match target {
+                    ForTarget::Declaration(DeclarationKind::Var, pattern) => pattern.bound_names(),
+                    _ => Vec::new(),
+                };
+                names.extend(body.var_declared_names());
+                names
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Class {
+    pub name: Option<String>,
+    pub superclass: Option<Expression>,
+    pub constructor: Option<(Vec<Parameter>, Vec<Statement>)>,
+    pub members: Vec<ClassMember>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ClassMember {
+    Method {
+        key: ClassKey,
+        kind: MethodKind,
+        is_static: bool,
+        parameters: Vec<Parameter>,
+        body: Vec<Statement>,
+    },
+    Field {
+        key: ClassKey,
+        initializer: Option<Expression>,
+        is_static: bool,
+    },
+    StaticBlock(Vec<Statement>),
+}
+
+/// The name of a class member.
+#[derive(Debug, Clone)]
+pub enum ClassKey {
+    Named(String),        // `a`, `"a"` or `1`
+    Computed(Expression), // `[expression]`
+    Private(String),      // `#a`
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MethodKind {
+    Method,
+    Getter,
+    Setter,
+}
+
+#[derive(Debug, Clone)]
+pub struct Declarator {
+    pub pattern: Pattern,
+    pub initializer: Option<Expression>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub pattern: Pattern,
+    pub default: Option<Expression>,
+    pub rest: bool, // `...name`, always the last parameter
+}
+
+/// The target of a declaration, parameter or assignment, which may take
+/// the assigned value apart.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Identifier(String),
+    Member(Expression), // `obj.key` or `obj[key]`, only in assignments
+    Array {
+        elements: Vec<Option<PatternElement>>, // `None` for a hole, e.g. `[a, , b]`
+        rest: Option<Box<Pattern>>,            // `...rest`
+        span: Span,                            // From `[` up to `]`
+    },
+    Object {
+        properties: Vec<PatternProperty>,
+        rest: Option<Box<Pattern>>, // `...rest`, always an identifier or member
+        span: Span,                 // From `{` up to `}`
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct PatternElement {
+    pub target: Pattern,
+    pub default: Option<Expression>, // Used when the value is missing
+}
+
+/// `key: target = default`, or the shorthand `target = default`.
+#[derive(Debug, Clone)]
+pub struct PatternProperty {
+    pub key: Expression, // A string literal for `a: b` and `a`
+    pub computed: bool,  // true for `[key]: b`
+    pub value: PatternElement,
+}
+
+impl Pattern {
+    /// The names of the variables the pattern binds, in source order.
+    pub fn bound_names(&self) -> Vec<String> {
+        match self {
+            Pattern::Identifier(name) => vec![name.to_string()],
+            Pattern::Member(_) => Vec::new(),
+            Pattern::Array { elements, rest, .. } => elements
+                .iter()
+                .flatten()
+                .map(|element| &element.target)
+                .chain(rest.as_deref())
+                .flat_map(Pattern::bound_names)
+                .collect(),
+            Pattern::Object {
+                properties, rest, ..
+            } => properties
+                .iter()
+                .map(|property| &property.value.target)
+                .chain(rest.as_deref())
+                .flat_map(Pattern::bound_names)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PropertyDefinition {
+    Property(String, Expression), // `key: value`
+    Spread(Expression),           // `...object`
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeclarationKind {
+    Var,
+    Let,
+    Const,
+}
+
+impl DeclarationKind {
+    /// Whether the declaration is block scoped, with a temporal dead zone
+    /// before it and no redeclaration allowed in the same block.
+    pub fn is_lexical(self) -> bool {
+        self != DeclarationKind::Var
+    }
+}
+
+/// The left-hand side of a `for...in` or `for...of` head.
+#[derive(Debug, Clone)]
+pub enum ForTarget {
+    Declaration(DeclarationKind, Pattern), // `for (const x of xs)` or `for (const [k, v] of xs)`
+    Variable(String),                      // `for (x of xs)`
 }
 
 #[derive(Debug, Clone)]
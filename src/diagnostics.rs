@@ -0,0 +1,252 @@
+use std::fmt::Write;
+
+use crate::error::{JustError, Span};
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// An error together with the source it was raised for, ready to be shown
+/// to a person or handed to a tool.
+pub struct Diagnostic<'a> {
+    error: &'a JustError,
+    source: &'a str,
+    path: Option<&'a str>,
+    hint: Option<String>,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// `source` must be the text that was passed to `Lexer::new`, since spans
+    /// are byte offsets into it.
+    pub fn new(error: &'a JustError, source: &'a str) -> Self {
+        Self {
+            error,
+            source,
+            path: None,
+            hint: default_hint(error).map(str::to_string),
+        }
+    }
+
+    /// Names the file the source was read from in the rendered location.
+    pub fn with_path(mut self, path: &'a str) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    /// Renders the error with the offending source line and a caret
+    /// underline, using ANSI colors when `color` is set.
+    ///
+    /// ```text
+    /// SyntaxError: Missing initializer in const declaration
+    ///  --> script.js:1:7
+    ///   |
+    /// 1 | const x;
+    ///   |       ^
+    ///   = hint: declare it with `let` if it is assigned later
+    /// ```
+    pub fn render(&self, color: bool) -> String {
+        let paint = |style: &'static str| if color { style } else { "" };
+        let reset = paint(RESET);
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "{}{}{}: {}{}{}",
+            paint(RED),
+            self.error.name(),
+            reset,
+            paint(BOLD),
+            self.error.message(),
+            reset
+        );
+
+        let gutter = match self.span() {
+            Some(span) => {
+                let gutter = " ".repeat(span.line.to_string().len());
+                let (text, start_col, width) = self.line_excerpt(span);
+
+                let _ = writeln!(
+                    out,
+                    "{}{}-->{} {}",
+                    gutter,
+                    paint(BLUE),
+                    reset,
+                    self.location(span)
+                );
+                let _ = writeln!(out, "{} {}|{}", gutter, paint(BLUE), reset);
+                let _ = writeln!(out, "{}{} |{} {}", paint(BLUE), span.line, reset, text);
+                let _ = writeln!(
+                    out,
+                    "{} {}|{} {}{}{}{}",
+                    gutter,
+                    paint(BLUE),
+                    reset,
+                    " ".repeat(start_col),
+                    paint(RED),
+                    "^".repeat(width),
+                    reset
+                );
+
+                gutter
+            }
+            None => String::new(),
+        };
+
+        if let Some(hint) = &self.hint {
+            let _ = writeln!(
+                out,
+                "{} {}={} {}hint{}: {}",
+                gutter,
+                paint(BLUE),
+                reset,
+                paint(CYAN),
+                reset,
+                hint
+            );
+        }
+
+        out
+    }
+
+    /// Renders the error as a single-line JSON object. Location fields are
+    /// `null` when the error has no span.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{");
+
+        let _ = write!(
+            out,
+            "\"kind\":{},\"message\":{}",
+            json_string(self.error.name()),
+            json_string(self.error.message())
+        );
+
+        match self.path {
+            Some(path) => {
+                let _ = write!(out, ",\"path\":{}", json_string(path));
+            }
+            None => out.push_str(",\"path\":null"),
+        }
+
+        match self.span() {
+            Some(span) => {
+                let (text, _, _) = self.line_excerpt(span);
+                let _ = write!(
+                    out,
+                    ",\"line\":{},\"column\":{},\"start\":{},\"end\":{},\"snippet\":{}",
+                    span.line,
+                    span.col,
+                    span.start_pos,
+                    span.end_pos,
+                    json_string(text)
+                );
+            }
+            None => out.push_str(
+                ",\"line\":null,\"column\":null,\"start\":null,\"end\":null,\"snippet\":null",
+            ),
+        }
+
+        match &self.hint {
+            Some(hint) => {
+                let _ = write!(out, ",\"hint\":{}", json_string(hint));
+            }
+            None => out.push_str(",\"hint\":null"),
+        }
+
+        out.push('}');
+        out
+    }
+
+    /// The error's span, if it actually lies within `source`.
+    fn span(&self) -> Option<Span> {
+        self.error
+            .span()
+            .filter(|span| span.start_pos <= self.source.len())
+    }
+
+    fn location(&self, span: Span) -> String {
+        match self.path {
+            Some(path) => format!("{}:{}:{}", path, span.line, span.col),
+            None => format!("{}:{}", span.line, span.col),
+        }
+    }
+
+    /// Returns the source line `span` starts on, the number of characters
+    /// before the span on that line, and how many characters of the line the
+    /// span covers (at least one, so there is always a caret).
+    fn line_excerpt(&self, span: Span) -> (&'a str, usize, usize) {
+        let source = self.source;
+        let line_start = source[..span.start_pos]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let line_end = source[span.start_pos..]
+            .find('\n')
+            .map_or(source.len(), |index| span.start_pos + index);
+        let text = source[line_start..line_end].trim_end_matches('\r');
+
+        let start_col = source[line_start..span.start_pos].chars().count();
+        let end = span.end_pos.clamp(span.start_pos, line_end);
+        let width = source[span.start_pos..end].chars().count().max(1);
+
+        (text, start_col, width)
+    }
+}
+
+/// Suggests a fix for errors that have a well-known cause.
+fn default_hint(error: &JustError) -> Option<&'static str> {
+    let message = error.message();
+
+    match error {
+        JustError::Lexical { .. } if message == "Unterminated string" => {
+            Some("strings must be closed with `\"` on the line they start on")
+        }
+        JustError::Syntax { .. } if message.starts_with("Missing initializer in const") => {
+            Some("declare it with `let` if it is assigned later")
+        }
+        JustError::Syntax { .. } if message.ends_with("has already been declared") => {
+            Some("use a different name, or assign to the existing variable")
+        }
+        JustError::Syntax { .. } if message.starts_with("Illegal break") => {
+            Some("`break` can only be used inside a loop or a labeled block")
+        }
+        JustError::Syntax { .. } if message.starts_with("Illegal continue") => {
+            Some("`continue` can only be used inside a loop")
+        }
+        JustError::Reference { .. } if message.ends_with("is not defined") => {
+            Some("declare it with `let`, `const` or `function` before using it")
+        }
+        JustError::Reference { .. } if message.ends_with("before initialization") => {
+            Some("move the declaration above its first use")
+        }
+        JustError::Type { .. } if message == "Assignment to constant variable." => {
+            Some("declare it with `let` if it needs to change")
+        }
+        JustError::Type { .. } if message.ends_with("is not a function") => {
+            Some("only functions can be called")
+        }
+        _ => None,
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
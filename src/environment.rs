@@ -1,20 +1,97 @@
-use std::{collections::HashMap, fmt};
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
 
-use crate::ast::Statement;
+use crate::{
+    array::ArrayObject,
+    ast::{Expression, Parameter, Statement},
+    error::JustError,
+    interpreter::Interpreter,
+    object::Object,
+    operations::number_to_string,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 
 pub struct FunctionExpression {
-    pub parameters: Vec<String>,
+    pub parameters: Vec<Parameter>,
     pub body: Vec<Statement>,
+    /// The scope the function was defined in, shared with every other
+    /// closure created in it.
+    pub closure: Rc<RefCell<Environment>>,
+    /// The function's own properties, such as `prototype`.
+    pub object: Rc<RefCell<Object>>,
+    /// Set when the function is the constructor of a class.
+    pub class: Option<ClassInfo>,
+    /// Arrow functions have no `this` or `arguments` of their own and
+    /// cannot be used as constructors.
+    pub arrow: bool,
 }
 
 impl FunctionExpression {
-    pub fn new(parameters: Vec<String>, body: Vec<Statement>) -> Self {
-        Self { parameters, body }
+    pub fn new(
+        parameters: Vec<Parameter>,
+        body: Vec<Statement>,
+        closure: Rc<RefCell<Environment>>,
+    ) -> Self {
+        Self {
+            parameters,
+            body,
+            closure,
+            object: Rc::new(RefCell::new(Object::new())),
+            class: None,
+            arrow: false,
+        }
+    }
+}
+
+/// What a class constructor needs besides its own body.
+#[derive(Debug, Clone)]
+pub struct ClassInfo {
+    pub name: Option<String>,
+    /// The constructor of the class it `extends`.
+    pub parent: Option<Rc<FunctionExpression>>,
+    /// Whether the class `extends` anything, even `null`. A derived
+    /// constructor has no `this` until it calls `super()`.
+    pub derived: bool,
+    /// Whether the class spells out a constructor. A derived class without
+    /// one passes its arguments straight on to the parent's.
+    pub has_constructor: bool,
+    /// Private methods, installed on every instance before its fields.
+    pub private_methods: Vec<(String, Value)>,
+    /// Instance fields in declaration order, initialized when a base class
+    /// constructor starts or when `super()` returns in a derived one.
+    pub fields: Vec<(FieldKey, Option<Expression>)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum FieldKey {
+    Public(String),
+    /// A private name, already resolved to its key in `Object`'s private
+    /// fields.
+    Private(String),
+}
+
+// The closure usually contains the function itself, so it is left out to
+// keep the output finite.
+impl fmt::Debug for FunctionExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FunctionExpression")
+            .field("parameters", &self.parameters)
+            .field("body", &self.body)
+            .finish_non_exhaustive()
     }
 }
 
+/// The signature of functions implemented in Rust: the interpreter, the
+/// `this` value and the arguments.
+pub type NativeFn = fn(&mut Interpreter, Value, Vec<Value>) -> Result<Value, JustError>;
+
+/// A built-in function, e.g. `Array.prototype.push`.
+#[derive(Debug, Clone, Copy)]
+pub struct NativeFunction {
+    pub name: &'static str,
+    pub function: NativeFn,
+}
+
 #[derive(Debug, Clone)]
 
 pub enum Value {
@@ -22,25 +99,208 @@ pub enum Value {
     String(String),
     Boolean(bool),
     Null,
-    Function(Box<FunctionExpression>),
+    Undefined,
+    Function(Rc<FunctionExpression>),
+    NativeFunction(NativeFunction),
+    Object(Rc<RefCell<Object>>),
+    Array(Rc<RefCell<ArrayObject>>),
 }
 
-impl fmt::Display for Value {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// How deeply nested objects are printed before they are abbreviated, which
+/// also keeps objects that contain themselves printable.
+const INSPECT_DEPTH: usize = 2;
+
+impl Value {
+    fn inspect(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
         match self {
-            Value::Number(n) => write!(f, "{}", n),
+            // Unlike ToString, inspecting keeps the sign of -0.
+            Value::Number(n) if *n == 0.0 && n.is_sign_negative() => write!(f, "-0"),
+            Value::Number(n) => write!(f, "{}", number_to_string(*n)),
             Value::String(s) => write!(f, "\"{}\"", s),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::Null => write!(f, "null"),
-            Value::Function(_) => write!(f, "[Function]"),
+            Value::Undefined => write!(f, "undefined"),
+            Value::Function(function) => match &function.class {
+                Some(ClassInfo {
+                    name: Some(name), ..
+                }) => write!(f, "[class {}]", name),
+                Some(_) => write!(f, "[class (anonymous)]"),
+                None => write!(f, "[Function]"),
+            },
+            Value::NativeFunction(native) => write!(f, "[Function: {}]", native.name),
+            Value::Array(array) => {
+                let array = array.borrow();
+                let keys = array.properties.keys();
+
+                if array.elements.is_empty() && keys.is_empty() {
+                    return write!(f, "[]");
+                }
+                if depth > INSPECT_DEPTH {
+                    return write!(f, "[Array]");
+                }
+
+                write!(f, "[ ")?;
+                let mut separator = "";
+                let mut elements = array.elements.iter().peekable();
+                while let Some(element) = elements.next() {
+                    write!(f, "{}", separator)?;
+                    separator = ", ";
+
+                    match element {
+                        Some(element) => element.inspect(f, depth + 1)?,
+                        None => {
+                            // A run of holes prints as one entry.
+                            let mut holes = 1;
+                            while elements.next_if(|element| element.is_none()).is_some() {
+                                holes += 1;
+                            }
+                            let plural = if holes == 1 { "" } else { "s" };
+                            write!(f, "<{} empty item{}>", holes, plural)?;
+                        }
+                    }
+                }
+                for key in &keys {
+                    write!(f, "{}", separator)?;
+                    separator = ", ";
+                    inspect_property(f, key, array.properties.get(key), depth)?;
+                }
+                write!(f, " ]")
+            }
+            Value::Object(object) => {
+                let object = object.borrow();
+                let keys = object.keys();
+
+                if keys.is_empty() {
+                    return write!(f, "{{}}");
+                }
+                if depth > INSPECT_DEPTH {
+                    return write!(f, "[Object]");
+                }
+
+                write!(f, "{{ ")?;
+                for (index, key) in keys.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    inspect_property(f, key, object.get(key), depth)?;
+                }
+                write!(f, " }}")
+            }
         }
     }
 }
 
+impl Value {
+    /// The Strict Equality Comparison (`===`).
+    pub fn strict_equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Null, Value::Null) | (Value::Undefined, Value::Undefined) => true,
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            (Value::NativeFunction(a), Value::NativeFunction(b)) => {
+                std::ptr::fn_addr_eq(a.function, b.function)
+            }
+            (Value::Object(a), Value::Object(b)) => Rc::ptr_eq(a, b),
+            (Value::Array(a), Value::Array(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// SameValue (`Object.is`): SameValueZero, except that `0` and `-0`
+    /// differ.
+    pub fn same_value(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) if *a == 0.0 && *b == 0.0 => {
+                a.is_sign_negative() == b.is_sign_negative()
+            }
+            _ => self.same_value_zero(other),
+        }
+    }
+
+    /// SameValueZero: strict equality, except that `NaN` equals itself.
+    pub fn same_value_zero(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) if a.is_nan() && b.is_nan() => true,
+            _ => self.strict_equals(other),
+        }
+    }
+}
+
+/// Drops `values` one at a time instead of recursively. Arrays and objects
+/// that nothing else refers to are taken apart and their contents queued,
+/// so freeing a deeply nested structure, like a long linked list, cannot
+/// overflow the native stack.
+pub fn drop_values(mut values: Vec<Value>) {
+    while let Some(value) = values.pop() {
+        match value {
+            Value::Array(array) => {
+                if let Some(array) = Rc::into_inner(array) {
+                    values.extend(array.into_inner().take_values());
+                }
+            }
+            Value::Object(object) => {
+                if let Some(object) = Rc::into_inner(object) {
+                    values.extend(object.into_inner().take_values());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inspect(f, 0)
+    }
+}
+
+/// Writes the `key: value` entry of an inspected object or array. An
+/// accessor property has no value to show.
+fn inspect_property(
+    f: &mut fmt::Formatter<'_>,
+    key: &str,
+    value: Option<Value>,
+    depth: usize,
+) -> fmt::Result {
+    if is_identifier(key) {
+        write!(f, "{}: ", key)?;
+    } else {
+        write!(f, "\"{}\": ", key)?;
+    }
+
+    match value {
+        Some(value) => value.inspect(f, depth + 1),
+        None => Ok(()),
+    }
+}
+
+/// Whether `key` can be written as a property name without quotes.
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' || c == '$' => {
+            chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        }
+        _ => false,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Environment {
-    values: HashMap<String, Value>,
-    pub parent: Option<Box<Environment>>,
+    values: HashMap<String, Binding>,
+    pub parent: Option<Rc<RefCell<Environment>>>,
+}
+
+/// A variable's slot. The value is `None` while a `let`, `const` or `class`
+/// binding is in its temporal dead zone.
+#[derive(Debug, Clone)]
+struct Binding {
+    value: Option<Value>,
+    mutable: bool,
 }
 
 impl Environment {
@@ -51,39 +311,162 @@ impl Environment {
         }
     }
 
-    pub fn with_parent(parent: Environment) -> Self {
+    pub fn with_parent(parent: Rc<RefCell<Environment>>) -> Self {
         Self {
             values: HashMap::new(),
-            parent: Some(Box::new(parent)),
+            parent: Some(parent),
         }
     }
 
     pub fn define(&mut self, name: String, value: Value) {
-        self.values.insert(name, value);
+        let value = Some(value);
+        self.values.insert(
+            name,
+            Binding {
+                value,
+                mutable: true,
+            },
+        );
+    }
+
+    /// Defines a binding that cannot be assigned to, like a `const`.
+    pub fn define_constant(&mut self, name: String, value: Value) {
+        let value = Some(value);
+        self.values.insert(
+            name,
+            Binding {
+                value,
+                mutable: false,
+            },
+        );
     }
 
-    pub fn get(&self, name: &String) -> Option<Value> {
-        if let Some(v) = self.values.get(name) {
-            return Some(v.clone());
+    /// Defines a `var` binding as `undefined`, unless the scope already has
+    /// one by that name, such as a parameter or an earlier `var`.
+    pub fn define_var(&mut self, name: String) {
+        self.values.entry(name).or_insert(Binding {
+            value: Some(Value::Undefined),
+            mutable: true,
+        });
+    }
+
+    /// Creates a binding that cannot be used until its declaration runs and
+    /// defines it, like a `let` before the `let` statement.
+    pub fn declare(&mut self, name: String) {
+        let value = None;
+        self.values.insert(
+            name,
+            Binding {
+                value,
+                mutable: true,
+            },
+        );
+    }
+
+    /// Looks up a binding's value. Bindings in their temporal dead zone
+    /// read as absent.
+    pub fn get(&self, name: &str) -> Option<Value> {
+        if let Some(binding) = self.values.get(name) {
+            return binding.value.clone();
         }
 
         if let Some(parent) = &self.parent {
-            return parent.get(name);
+            return parent.borrow().get(name);
         }
 
         None
     }
 
-    pub fn assign(&mut self, name: String, value: Value) -> Result<(), String> {
-        if self.values.contains_key(&name) {
-            self.values.insert(name, value);
-            return Ok(());
+    /// Whether `name` is declared in this scope or an enclosing one, even if
+    /// it is still in its temporal dead zone.
+    pub fn has(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+            || self
+                .parent
+                .as_ref()
+                .is_some_and(|parent| parent.borrow().has(name))
+    }
+
+    /// Reads a variable as a script does, where an undeclared name or one
+    /// in its temporal dead zone is an error.
+    pub fn lookup(&self, name: &str) -> Result<Value, JustError> {
+        if let Some(binding) = self.values.get(name) {
+            return binding.value.clone().ok_or_else(|| uninitialized(name));
+        }
+
+        if let Some(parent) = &self.parent {
+            return parent.borrow().lookup(name);
         }
 
-        if let Some(parent) = &mut self.parent {
-            return parent.assign(name, value);
+        Err(JustError::reference(format!("{} is not defined", name)))
+    }
+
+    /// Initializes the nearest binding named `name` if it is still in its
+    /// temporal dead zone, returning whether it was.
+    pub fn initialize(&mut self, name: &str, value: Value) -> bool {
+        if let Some(binding) = self.values.get_mut(name) {
+            if binding.value.is_some() {
+                return false;
+            }
+            binding.value = Some(value);
+            return true;
+        }
+
+        match &self.parent {
+            Some(parent) => parent.borrow_mut().initialize(name, value),
+            None => false,
+        }
+    }
+
+    pub fn assign(&mut self, name: String, value: Value) -> Result<(), JustError> {
+        if let Some(binding) = self.values.get_mut(&name) {
+            return match binding {
+                Binding { value: None, .. } => Err(uninitialized(&name)),
+                Binding { mutable: false, .. } => {
+                    Err(JustError::type_error("Assignment to constant variable."))
+                }
+                Binding { value: slot, .. } => {
+                    *slot = Some(value);
+                    Ok(())
+                }
+            };
         }
 
-        Err(format!(""))
+        if let Some(parent) = &self.parent {
+            return parent.borrow_mut().assign(name, value);
+        }
+
+        Err(JustError::reference(format!("{} is not defined", name)))
+    }
+}
+
+fn uninitialized(name: &str) -> JustError {
+    JustError::reference(format!("Cannot access '{}' before initialization", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_value_and_same_value_zero() {
+        let cases = [
+            (0.0, -0.0, false, true),
+            (-0.0, -0.0, true, true),
+            (f64::NAN, f64::NAN, true, true),
+            (1.0, 1.0, true, true),
+            (1.0, 2.0, false, false),
+        ];
+        for (x, y, same_value, same_value_zero) in cases {
+            let (x, y) = (Value::Number(x), Value::Number(y));
+            assert_eq!(x.same_value(&y), same_value, "SameValue({}, {})", x, y);
+            assert_eq!(
+                x.same_value_zero(&y),
+                same_value_zero,
+                "SameValueZero({}, {})",
+                x,
+                y
+            );
+        }
     }
 }
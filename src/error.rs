@@ -0,0 +1,146 @@
+use std::fmt;
+
+use crate::lexer::Token;
+
+/// A region of the source text, in the same coordinates `Token` records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub col: usize,
+    pub start_pos: usize,
+    pub end_pos: usize,
+}
+
+impl Span {
+    /// Returns a span starting where `self` starts and ending where `other`
+    /// ends.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            end_pos: other.end_pos,
+            ..self
+        }
+    }
+}
+
+impl From<&Token> for Span {
+    fn from(token: &Token) -> Self {
+        Self {
+            line: token.line,
+            col: token.col,
+            start_pos: token.start_pos,
+            end_pos: token.end_pos,
+        }
+    }
+}
+
+/// Every error the lexer, parser and interpreter can produce.
+///
+/// Errors raised while running a script may not know where they happened
+/// (e.g. inside `Environment`), in which case the interpreter attaches the
+/// span of the closest expression it is evaluating.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JustError {
+    Lexical { message: String, span: Option<Span> },
+    Syntax { message: String, span: Option<Span> },
+    Reference { message: String, span: Option<Span> },
+    Type { message: String, span: Option<Span> },
+    Range { message: String, span: Option<Span> },
+}
+
+impl JustError {
+    pub fn lexical(message: impl Into<String>, span: Span) -> Self {
+        Self::Lexical {
+            message: message.into(),
+            span: Some(span),
+        }
+    }
+
+    pub fn syntax(message: impl Into<String>, token: &Token) -> Self {
+        Self::Syntax {
+            message: message.into(),
+            span: Some(token.into()),
+        }
+    }
+
+    pub fn reference(message: impl Into<String>) -> Self {
+        Self::Reference {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn type_error(message: impl Into<String>) -> Self {
+        Self::Type {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn range(message: impl Into<String>) -> Self {
+        Self::Range {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    /// The name JavaScript gives this kind of error, e.g. `TypeError`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Lexical { .. } => "LexicalError",
+            Self::Syntax { .. } => "SyntaxError",
+            Self::Reference { .. } => "ReferenceError",
+            Self::Type { .. } => "TypeError",
+            Self::Range { .. } => "RangeError",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Lexical { message, .. }
+            | Self::Syntax { message, .. }
+            | Self::Reference { message, .. }
+            | Self::Type { message, .. }
+            | Self::Range { message, .. } => message,
+        }
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::Lexical { span, .. }
+            | Self::Syntax { span, .. }
+            | Self::Reference { span, .. }
+            | Self::Type { span, .. }
+            | Self::Range { span, .. } => *span,
+        }
+    }
+
+    /// Attaches `span` to the error unless it already points somewhere more
+    /// precise.
+    pub fn with_span(mut self, new_span: Span) -> Self {
+        match &mut self {
+            Self::Lexical { span, .. }
+            | Self::Syntax { span, .. }
+            | Self::Reference { span, .. }
+            | Self::Type { span, .. }
+            | Self::Range { span, .. } => {
+                span.get_or_insert(new_span);
+            }
+        }
+
+        self
+    }
+}
+
+impl fmt::Display for JustError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name(), self.message())?;
+
+        if let Some(span) = self.span() {
+            write!(f, " at {}:{}", span.line, span.col)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for JustError {}
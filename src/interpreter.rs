@@ -1,22 +1,109 @@
+use std::{cell::RefCell, rc::Rc};
+
 use crate::{
-    ast::{Expression, Literal, Statement},
-    environment::{Environment, FunctionExpression, Value},
+    array,
+    ast::{
+        Class, ClassKey, ClassMember, DeclarationKind, Declarator, Expression, ForTarget, Literal,
+        MethodKind, Parameter, Pattern, PatternElement, PatternProperty, PropertyDefinition,
+        Statement,
+    },
+    environment::{ClassInfo, Environment, FieldKey, FunctionExpression, Value},
+    error::{JustError, Span},
     lexer::TokenType,
+    object::{self, array_index, enumerable_keys, Object, Property},
+    operations::{
+        exponentiate, is_less_than, is_loosely_equal, to_boolean, to_int32, to_number, to_numeric,
+        to_primitive, to_property_key, to_string, to_uint32, type_of, Hint,
+    },
+    stack::{StackGuard, DEFAULT_STACK_SIZE},
 };
 
+// Class bodies keep some of their state in scope bindings named after
+// keywords, which no user binding can shadow:
+//
+// - `super` is the object `super.x` looks `x` up on: the parent's prototype
+//   in instance methods, the parent itself in static ones.
+// - `class` is bound while a class constructor runs, for `super(...)` to
+//   find the class it is called in.
+// - `new` holds the object a derived constructor is creating, which
+//   `super(...)` binds to `this`.
+const SUPER_BINDING: &str = "super";
+const CLASS_BINDING: &str = "class";
+const INSTANCE_BINDING: &str = "new";
+
+const UNINITIALIZED_THIS: &str = "Must call super constructor in derived class before accessing 'this' or returning from derived constructor";
+
+/// The completion record produced by executing a statement.
+///
+/// Anything other than `Normal` is an abrupt completion that has to unwind
+/// through the enclosing blocks and loops until something consumes it.
+#[derive(Debug)]
+enum Completion {
+    Normal,
+    Return(Value),
+    Break(Option<String>),
+    Continue(Option<String>),
+}
+
 pub struct Interpreter {
-    environment: Environment,
+    environment: Rc<RefCell<Environment>>,
+    /// `Object.prototype`, which object literals and function prototypes
+    /// inherit from.
+    object_prototype: Rc<RefCell<Object>>,
+    /// How many classes have been defined, used to tell apart the private
+    /// names of different classes.
+    class_count: usize,
+    /// Stops a runaway recursion with a RangeError before it overflows the
+    /// native stack.
+    pub(crate) stack: StackGuard,
+    /// The arrays being joined, so that an array containing itself joins as
+    /// `""` where it recurs rather than forever.
+    pub(crate) joining: Vec<array::Array>,
+}
+
+/// A property key as a member expression names it.
+enum PropertyKey {
+    Public(String),
+    /// A `#name`, with the key it resolved to in the enclosing class.
+    Private {
+        name: String,
+        key: String,
+    },
 }
 
 impl Interpreter {
     pub fn new() -> Self {
+        let object_prototype = object::prototype();
+
+        let mut globals = Environment::new();
+        globals.define_constant("undefined".to_string(), Value::Undefined);
+        // Scripts run with `this` as `undefined`, so only a derived
+        // constructor that has not called `super()` yet can lack one.
+        globals.define_constant("this".to_string(), Value::Undefined);
+        globals.define("Object".to_string(), object::constructor(&object_prototype));
+
         Self {
-            environment: Environment::new(),
+            environment: Rc::new(RefCell::new(globals)),
+            object_prototype,
+            class_count: 0,
+            stack: StackGuard::new(DEFAULT_STACK_SIZE),
+            joining: Vec::new(),
         }
     }
 
-    pub fn interpret(&mut self, statements: Vec<Statement>) -> Result<Option<Value>, String> {
+    /// Sets how much stack the thread running the interpreter has. Without
+    /// it the interpreter assumes the 2 MiB a spawned thread gets, and
+    /// running it on a smaller stack can still overflow.
+    pub fn with_stack_size(mut self, size: usize) -> Self {
+        self.stack = StackGuard::new(size);
+        self
+    }
+
+    pub fn interpret(&mut self, statements: Vec<Statement>) -> Result<Option<Value>, JustError> {
+        self.stack.reset();
         let mut last_value = None;
+        self.hoist_var_declarations(&statements);
+        self.hoist_declarations(&statements);
 
         for statement in statements {
             match statement {
@@ -25,67 +112,381 @@ impl Interpreter {
 
                     last_value = Some(value);
                 }
-                _ => {
-                    self.execute(&statement)?;
-                }
+                Statement::FunctionDeclaration { .. } => {}
+                _ => match self.execute(&statement)? {
+                    Completion::Normal => {}
+                    completion => {
+                        unreachable!("the parser rejects {:?} at the top level", completion)
+                    }
+                },
             }
         }
 
         Ok(last_value)
     }
 
-    fn execute(&mut self, statement: &Statement) -> Result<(), String> {
+    fn execute(&mut self, statement: &Statement) -> Result<Completion, JustError> {
+        self.stack.check()?;
+
         match statement {
             Statement::Expression(expr) => {
                 self.evaluate(expr)?;
-                Ok(())
+                Ok(Completion::Normal)
+            }
+            Statement::VariableDeclaration { kind, declarators } => {
+                self.declare_variables(*kind, declarators)?;
+                Ok(Completion::Normal)
             }
-            Statement::VariableDeclaration {
-                name,
-                initializer,
-                can_reassign,
-            } => match initializer {
-                Some(expr) => {
-                    let value = self.evaluate(expr)?;
-                    self.environment.define(name.to_string(), value);
-                    Ok(())
-                }
-                None => {
-                    if *can_reassign {
-                        self.environment.define(name.to_string(), Value::Null);
-                        Ok(())
-                    } else {
-                        Err("const missing initializer".to_string())
-                    }
-                }
-            },
             Statement::FunctionDeclaration {
                 name,
                 parameters,
                 body,
             } => {
-                self.environment.define(
-                    name.to_string(),
-                    Value::Function(Box::new(FunctionExpression::new(
-                        parameters.clone(),
-                        body.to_vec(),
-                    ))),
-                );
-                Ok(())
+                let function = self.create_function(parameters.clone(), body.to_vec());
+                self.environment
+                    .borrow_mut()
+                    .define(name.to_string(), function);
+                Ok(Completion::Normal)
             }
-            Statement::Block(statements) => {
-                self.enter_scope();
-                for statement in statements {
-                    self.execute(statement)?
+            Statement::Block(statements) => self.execute_block(statements),
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if to_boolean(&self.evaluate(condition)?) {
+                    self.execute(then_branch)
+                } else if let Some(else_branch) = else_branch {
+                    self.execute(else_branch)
+                } else {
+                    Ok(Completion::Normal)
                 }
+            }
+            Statement::While { .. }
+            | Statement::For { .. }
+            | Statement::ForIn { .. }
+            | Statement::ForOf { .. } => self.execute_loop(statement, &[]),
+            Statement::Labeled { label, body } => self.execute_labeled(label, body),
+            Statement::Break(label) => Ok(Completion::Break(label.clone())),
+            Statement::Continue(label) => Ok(Completion::Continue(label.clone())),
+            Statement::Return(expr) => {
+                let value = match expr {
+                    Some(expr) => self.evaluate(expr)?,
+                    None => Value::Undefined,
+                };
+
+                Ok(Completion::Return(value))
+            }
+            Statement::Class(class) => {
+                let constructor = self.define_class(class)?;
+                let name = class.name.clone().unwrap_or_default();
+                self.environment.borrow_mut().define(name, constructor);
+                Ok(Completion::Normal)
+            }
+        }
+    }
+
+    fn declare_variables(
+        &mut self,
+        kind: DeclarationKind,
+        declarators: &[Declarator],
+    ) -> Result<(), JustError> {
+        for Declarator {
+            pattern,
+            initializer,
+        } in declarators
+        {
+            // A missing initializer on `const` or a pattern is rejected by
+            // the parser, and `var x;` leaves the hoisted binding alone.
+            let value = match initializer {
+                Some(expr) => self.evaluate(expr)?,
+                None if kind == DeclarationKind::Var => continue,
+                None => Value::Undefined,
+            };
+            self.bind_pattern(pattern, value, Some(kind))?;
+        }
+
+        Ok(())
+    }
+
+    /// Executes a labeled statement. A `break` to one of its labels ends it
+    /// normally.
+    fn execute_labeled(&mut self, label: &str, body: &Statement) -> Result<Completion, JustError> {
+        // Collect every label of `a: b: for (...)` so `continue a` and
+        // `continue b` both target the loop.
+        let mut labels = vec![label.to_string()];
+        let mut body = body;
+        while let Statement::Labeled { label, body: inner } = body {
+            labels.push(label.to_string());
+            body = inner;
+        }
+
+        let completion = match body {
+            Statement::While { .. }
+            | Statement::For { .. }
+            | Statement::ForIn { .. }
+            | Statement::ForOf { .. } => self.execute_loop(body, &labels)?,
+            _ => self.execute(body)?,
+        };
+
+        match completion {
+            Completion::Break(Some(label)) if labels.contains(&label) => Ok(Completion::Normal),
+            completion => Ok(completion),
+        }
+    }
+
+    /// Executes `statements` in a fresh scope, leaving it again even when a
+    /// statement fails or completes abruptly.
+    fn execute_block(&mut self, statements: &[Statement]) -> Result<Completion, JustError> {
+        self.enter_scope();
+        let result = self.execute_statements(statements);
+        self.exit_scope();
+        result
+    }
+
+    /// Executes a loop statement whose label set is `labels`. An unlabeled
+    /// `break` out of the loop completes it normally.
+    fn execute_loop(
+        &mut self,
+        statement: &Statement,
+        labels: &[String],
+    ) -> Result<Completion, JustError> {
+        let completion = match statement {
+            Statement::While { condition, body } => {
+                let mut completion = Completion::Normal;
+                while to_boolean(&self.evaluate(condition)?) {
+                    let result = self.execute(body)?;
+                    if !loop_continues(&result, labels) {
+                        completion = result;
+                        break;
+                    }
+                }
+
+                Ok(completion)
+            }
+            Statement::For {
+                initializer,
+                condition,
+                update,
+                body,
+            } => {
+                self.enter_scope();
+                let result =
+                    self.execute_for(initializer.as_deref(), condition, update, body, labels);
                 self.exit_scope();
-                Ok(())
+                result
+            }
+            Statement::ForIn {
+                target,
+                object,
+                body,
+            } => {
+                let keys = match self.evaluate(object)? {
+                    Value::String(s) => (0..s.encode_utf16().count())
+                        .map(|index| Value::String(index.to_string()))
+                        .collect(),
+                    Value::Object(object) => enumerable_keys(&object)
+                        .into_iter()
+                        .map(Value::String)
+                        .collect(),
+                    Value::Array(array) => {
+                        array::keys(&array).into_iter().map(Value::String).collect()
+                    }
+                    _ => Vec::new(),
+                };
+
+                self.execute_for_each(target, keys.into_iter(), body, labels)
+            }
+            Statement::ForOf {
+                target,
+                iterable,
+                body,
+            } => {
+                let values = iterate(self.evaluate(iterable)?)?;
+                self.execute_for_each(target, values, body, labels)
+            }
+            _ => unreachable!("execute_loop called with a non-loop statement"),
+        }?;
+
+        match completion {
+            Completion::Break(None) => Ok(Completion::Normal),
+            completion => Ok(completion),
+        }
+    }
+
+    /// Runs a classic `for (init; cond; update)` loop inside the loop scope
+    /// entered by the caller.
+    fn execute_for(
+        &mut self,
+        initializer: Option<&Statement>,
+        condition: &Option<Expression>,
+        update: &Option<Expression>,
+        body: &Statement,
+        labels: &[String],
+    ) -> Result<Completion, JustError> {
+        // Bindings declared with `let` in the head get a fresh copy for every
+        // iteration, so each iteration observes its own value.
+        let mut per_iteration = Vec::new();
+        if let Some(initializer) = initializer {
+            if let Statement::VariableDeclaration {
+                kind: DeclarationKind::Let,
+                declarators,
+            } = initializer
+            {
+                for declarator in declarators {
+                    per_iteration.extend(declarator.pattern.bound_names());
+                }
+            }
+
+            self.execute(initializer)?;
+        }
+
+        self.copy_iteration_bindings(&per_iteration);
+
+        loop {
+            if let Some(condition) = condition {
+                if !to_boolean(&self.evaluate(condition)?) {
+                    break;
+                }
+            }
+
+            let completion = self.execute(body)?;
+            if !loop_continues(&completion, labels) {
+                return Ok(completion);
+            }
+
+            self.copy_iteration_bindings(&per_iteration);
+
+            if let Some(update) = update {
+                self.evaluate(update)?;
+            }
+        }
+
+        Ok(Completion::Normal)
+    }
+
+    /// Replaces the current loop scope with a fresh one holding copies of
+    /// the bindings in `names` (CreatePerIterationEnvironment).
+    fn copy_iteration_bindings(&mut self, names: &[String]) {
+        if names.is_empty() {
+            return;
+        }
+
+        let values: Vec<_> = names
+            .iter()
+            .map(|name| {
+                let value = self.environment.borrow().get(name);
+                value.unwrap_or(Value::Undefined)
+            })
+            .collect();
+
+        self.exit_scope();
+        self.enter_scope();
+
+        for (name, value) in names.iter().zip(values) {
+            self.environment
+                .borrow_mut()
+                .define(name.to_string(), value);
+        }
+    }
+
+    /// Runs the body of a `for...in` / `for...of` loop once per item, binding
+    /// the item to `target` in a fresh scope each time.
+    fn execute_for_each(
+        &mut self,
+        target: &ForTarget,
+        items: impl Iterator<Item = Value>,
+        body: &Statement,
+        labels: &[String],
+    ) -> Result<Completion, JustError> {
+        for item in items {
+            self.enter_scope();
+            let result = match target {
+                ForTarget::Declaration(kind, pattern) => self
+                    .bind_pattern(pattern, item, Some(*kind))
+                    .and_then(|_| self.execute(body)),
+                ForTarget::Variable(name) => {
+                    let assigned = self.environment.borrow_mut().assign(name.to_string(), item);
+                    assigned.and_then(|_| self.execute(body))
+                }
+            };
+            self.exit_scope();
+
+            let completion = result?;
+            if !loop_continues(&completion, labels) {
+                return Ok(completion);
+            }
+        }
+
+        Ok(Completion::Normal)
+    }
+
+    /// Defines every `var` declared in a function or script body, nested
+    /// blocks included, in the current scope as `undefined`, so the names
+    /// can be read before their declaration runs.
+    fn hoist_var_declarations(&mut self, statements: &[Statement]) {
+        let mut scope = self.environment.borrow_mut();
+        for name in statements.iter().flat_map(Statement::var_declared_names) {
+            scope.define_var(name);
+        }
+    }
+
+    /// Puts the declarations among `statements` into the current scope ahead
+    /// of time. Functions are defined right away, so they can be called
+    /// above their declaration, while `let`, `const` and `class` bindings
+    /// stay uninitialized until their declaration runs, so that using one
+    /// early is an error rather than a lookup in an outer scope.
+    fn hoist_declarations(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            match statement {
+                Statement::VariableDeclaration { kind, declarators } if kind.is_lexical() => {
+                    for declarator in declarators {
+                        for name in declarator.pattern.bound_names() {
+                            self.environment.borrow_mut().declare(name);
+                        }
+                    }
+                }
+                Statement::Class(Class {
+                    name: Some(name), ..
+                }) => self.environment.borrow_mut().declare(name.to_string()),
+                Statement::FunctionDeclaration {
+                    name,
+                    parameters,
+                    body,
+                } => {
+                    let function = self.create_function(parameters.clone(), body.to_vec());
+                    self.environment
+                        .borrow_mut()
+                        .define(name.to_string(), function);
+                }
+                _ => {}
             }
-            _ => Err("Unknown statement".to_string()),
         }
     }
 
-    fn evaluate(&mut self, expr: &Expression) -> Result<Value, String> {
+    /// Executes `statements` in the current scope, stopping at the first
+    /// abrupt completion and handing it back to the caller.
+    fn execute_statements(&mut self, statements: &[Statement]) -> Result<Completion, JustError> {
+        self.hoist_declarations(statements);
+
+        for statement in statements {
+            // Function declarations have already been defined.
+            if let Statement::FunctionDeclaration { .. } = statement {
+                continue;
+            }
+
+            match self.execute(statement)? {
+                Completion::Normal => {}
+                completion => return Ok(completion),
+            }
+        }
+
+        Ok(Completion::Normal)
+    }
+
+    fn evaluate(&mut self, expr: &Expression) -> Result<Value, JustError> {
+        self.stack.check()?;
+
         match expr {
             Expression::Literal(literal) => match literal {
                 Literal::Number(n) => Ok(Value::Number(*n)),
@@ -94,119 +495,2001 @@ impl Interpreter {
                 Literal::Null => Ok(Value::Null),
             },
             Expression::Grouping(expr) => self.evaluate(expr),
-            Expression::Unary { operator, right } => {
-                let value = self.evaluate(right)?;
-                if operator == "!" {
-                    Ok(Value::Boolean(!is_truthy(&value)))
-                } else {
-                    let n = match value {
-                        Value::Number(v) => v * -1.0,
-                        _ => -1.0,
-                    };
-
-                    Ok(Value::Number(n))
+            Expression::Sequence(expressions) => {
+                let mut value = Value::Undefined;
+                for expression in expressions {
+                    value = self.evaluate(expression)?;
                 }
+                Ok(value)
             }
+            Expression::Unary { operator, right } => self.unary(operator, right),
             Expression::Binary {
                 left,
                 operator,
                 right,
+                span,
             } => {
                 let left = self.evaluate(left)?;
                 let right = self.evaluate(right)?;
 
-                match (operator, left, right) {
-                    (TokenType::Plus, Value::Number(a), Value::Number(b)) => {
-                        Ok(Value::Number(a + b))
+                self.binary_operation(operator, &left, &right)
+                    .map_err(|err| err.with_span(*span))
+            }
+            Expression::Update {
+                target,
+                operator,
+                prefix,
+                span,
+            } => self.update(target, operator, *prefix, *span),
+            Expression::Logical {
+                left,
+                operator,
+                right,
+            } => {
+                let left = self.evaluate(left)?;
+                if short_circuits(operator, &left) {
+                    Ok(left)
+                } else {
+                    self.evaluate(right)
+                }
+            }
+            Expression::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if to_boolean(&self.evaluate(condition)?) {
+                    self.evaluate(then_branch)
+                } else {
+                    self.evaluate(else_branch)
+                }
+            }
+            Expression::Variable { name, span } => self
+                .environment
+                .borrow()
+                .lookup(name)
+                .map_err(|err| err.with_span(*span)),
+            Expression::Assignment {
+                target,
+                operator,
+                value,
+                span,
+            } => self.assign(target, operator, value, *span),
+            Expression::Member {
+                object,
+                property,
+                computed,
+                span,
+            } => {
+                let object = self.evaluate(object)?;
+                let key = self
+                    .property_key(property, *computed)
+                    .map_err(|err| err.with_span(*span))?;
+                self.get_member(&object, &key)
+                    .map_err(|err| err.with_span(*span))
+            }
+            Expression::ArrayLiteral { elements, .. } => self.array_literal(elements),
+            Expression::ObjectLiteral { properties, .. } => self.object_literal(properties),
+            Expression::Call {
+                callee,
+                arguments,
+                span,
+            } => self.call(callee, arguments, *span),
+            Expression::New {
+                callee,
+                arguments,
+                span,
+            } => self.new_instance(callee, arguments, *span),
+            Expression::This(span) => self.lookup_this().map_err(|err| err.with_span(*span)),
+            Expression::PrivateName(_) => {
+                unreachable!("the parser only produces private names as member properties")
+            }
+            Expression::Spread { .. } => {
+                unreachable!("the parser only produces spreads in arguments and array literals")
+            }
+            Expression::Class(class) => self.define_class(class),
+            Expression::Function {
+                name,
+                parameters,
+                body,
+            } => Ok(self.function_expression(name.as_deref(), parameters, body)),
+            Expression::Arrow { parameters, body } => {
+                let mut function = FunctionExpression::new(
+                    parameters.clone(),
+                    body.clone(),
+                    Rc::clone(&self.environment),
+                );
+                function.arrow = true;
+                Ok(Value::Function(Rc::new(function)))
+            }
+            Expression::SuperCall { arguments, span } => {
+                let arguments = self.evaluate_arguments(arguments)?;
+                self.super_call(arguments)
+                    .map_err(|err| err.with_span(*span))
+            }
+            Expression::SuperMember {
+                property,
+                computed,
+                span,
+            } => self.super_member(property, *computed, *span),
+        }
+    }
+
+    fn unary(&mut self, operator: &str, right: &Expression) -> Result<Value, JustError> {
+        match (operator, right) {
+            // An undeclared variable is `"undefined"` rather than an error.
+            ("typeof", Expression::Variable { name, .. })
+                if !self.environment.borrow().has(name) =>
+            {
+                return Ok(Value::String("undefined".to_string()));
+            }
+            ("delete", right) => return self.delete(right),
+            _ => {}
+        }
+
+        let value = self.evaluate(right)?;
+        match operator {
+            "typeof" => Ok(Value::String(type_of(&value).to_string())),
+            "!" => Ok(Value::Boolean(!to_boolean(&value))),
+            "void" => Ok(Value::Undefined),
+            "+" => Ok(Value::Number(to_number(self, &value)?)),
+            "-" => Ok(Value::Number(-to_numeric(self, &value)?)),
+            "~" => {
+                let n = to_int32(to_numeric(self, &value)?);
+                Ok(Value::Number(f64::from(!n)))
+            }
+            _ => unreachable!(
+                "the parser only produces !, void, typeof, delete, +, - and ~ operators"
+            ),
+        }
+    }
+
+    /// `delete target`, which removes a property and evaluates to whether
+    /// the property is gone. Anything but a property is left in place.
+    fn delete(&mut self, target: &Expression) -> Result<Value, JustError> {
+        match target {
+            Expression::Grouping(inner) => self.delete(inner),
+            Expression::Member {
+                object,
+                property,
+                computed,
+                span,
+            } => {
+                let object = self.evaluate(object)?;
+                let key = match self.property_key(property, *computed) {
+                    Ok(PropertyKey::Public(key)) => key,
+                    Ok(PropertyKey::Private { .. }) => {
+                        unreachable!("the parser rejects deleting a private field")
                     }
-                    (TokenType::Minus, Value::Number(a), Value::Number(b)) => {
-                        Ok(Value::Number(a - b))
+                    Err(err) => return Err(err.with_span(*span)),
+                };
+
+                let deleted = match &object {
+                    Value::Object(object) => {
+                        object.borrow_mut().delete(&key);
+                        true
                     }
-                    (TokenType::Star, Value::Number(a), Value::Number(b)) => {
-                        Ok(Value::Number(a * b))
+                    Value::Function(function) => {
+                        function.object.borrow_mut().delete(&key);
+                        true
                     }
-                    (TokenType::Power, Value::Number(a), Value::Number(b)) => {
-                        Ok(Value::Number(a.powf(b)))
+                    Value::Array(array) => array::delete_property(array, &key),
+                    Value::Null | Value::Undefined => {
+                        return Err(JustError::type_error(
+                            "Cannot convert undefined or null to object",
+                        )
+                        .with_span(*span))
                     }
-                    (TokenType::Percent, Value::Number(a), Value::Number(b)) => {
-                        Ok(Value::Number(a % b))
+                    _ => true,
+                };
+                Ok(Value::Boolean(deleted))
+            }
+            Expression::SuperMember { span, .. } => {
+                Err(JustError::reference("Unsupported reference to 'super'").with_span(*span))
+            }
+            // Declared variables cannot be deleted.
+            Expression::Variable { name, .. } => {
+                Ok(Value::Boolean(!self.environment.borrow().has(name)))
+            }
+            _ => {
+                self.evaluate(target)?;
+                Ok(Value::Boolean(true))
+            }
+        }
+    }
+
+    fn function_expression(
+        &mut self,
+        name: Option<&str>,
+        parameters: &[Parameter],
+        body: &[Statement],
+    ) -> Value {
+        match name {
+            // A named function expression can refer to itself by name from
+            // a scope of its own.
+            Some(name) => {
+                self.enter_scope();
+                let function = self.create_function(parameters.to_vec(), body.to_vec());
+                self.environment
+                    .borrow_mut()
+                    .define(name.to_string(), function.clone());
+                self.exit_scope();
+                function
+            }
+            None => self.create_function(parameters.to_vec(), body.to_vec()),
+        }
+    }
+
+    /// `x++`, `--x` and the like on a variable or member.
+    fn update(
+        &mut self,
+        target: &Pattern,
+        operator: &TokenType,
+        prefix: bool,
+        span: Span,
+    ) -> Result<Value, JustError> {
+        let step = if *operator == TokenType::PlusPlus {
+            1.0
+        } else {
+            -1.0
+        };
+
+        let old = match target {
+            Pattern::Identifier(name) => {
+                let old = self.environment.borrow().lookup(name);
+                let old = old
+                    .and_then(|old| to_numeric(self, &old))
+                    .map_err(|err| err.with_span(span))?;
+                self.environment
+                    .borrow_mut()
+                    .assign(name.to_string(), Value::Number(old + step))
+                    .map_err(|err| err.with_span(span))?;
+                old
+            }
+            Pattern::Member(Expression::Member {
+                object,
+                property,
+                computed,
+                ..
+            }) => {
+                let object = self.evaluate(object)?;
+                let key = self
+                    .property_key(property, *computed)
+                    .map_err(|err| err.with_span(span))?;
+                let old = self
+                    .get_member(&object, &key)
+                    .and_then(|old| to_numeric(self, &old))
+                    .map_err(|err| err.with_span(span))?;
+                self.set_member(&object, key, Value::Number(old + step))
+                    .map_err(|err| err.with_span(span))?;
+                old
+            }
+            _ => unreachable!("the parser only updates variables and members"),
+        };
+
+        Ok(Value::Number(if prefix { old + step } else { old }))
+    }
+
+    /// `target = value`, or a compound assignment like `target += value`.
+    fn assign(
+        &mut self,
+        target: &Pattern,
+        operator: &TokenType,
+        value: &Expression,
+        span: Span,
+    ) -> Result<Value, JustError> {
+        match target {
+            Pattern::Identifier(name) => {
+                let value = if *operator == TokenType::Equal {
+                    self.evaluate(value)?
+                } else {
+                    let current = self
+                        .environment
+                        .borrow()
+                        .lookup(name)
+                        .map_err(|err| err.with_span(span))?;
+                    match self
+                        .compound_assignment(operator, &current, value)
+                        .map_err(|err| err.with_span(span))?
+                    {
+                        Some(value) => value,
+                        None => return Ok(current),
                     }
-                    (TokenType::Slash, Value::Number(a), Value::Number(b)) => {
-                        if b == 0.0 {
-                            Err("Division by zero".to_string())
-                        } else {
-                            Ok(Value::Number(a / b))
-                        }
+                };
+                self.environment
+                    .borrow_mut()
+                    .assign(name.to_string(), value.clone())
+                    .map_err(|err| err.with_span(span))?;
+                Ok(value)
+            }
+            Pattern::Member(Expression::Member {
+                object,
+                property,
+                computed,
+                span,
+            }) => {
+                let object = self.evaluate(object)?;
+                let key = self
+                    .property_key(property, *computed)
+                    .map_err(|err| err.with_span(*span))?;
+                let value = if *operator == TokenType::Equal {
+                    self.evaluate(value)?
+                } else {
+                    let current = self
+                        .get_member(&object, &key)
+                        .map_err(|err| err.with_span(*span))?;
+                    match self
+                        .compound_assignment(operator, &current, value)
+                        .map_err(|err| err.with_span(*span))?
+                    {
+                        Some(value) => value,
+                        None => return Ok(current),
                     }
+                };
+                self.set_member(&object, key, value.clone())
+                    .map_err(|err| err.with_span(*span))?;
+                Ok(value)
+            }
+            pattern => {
+                let value = self.evaluate(value)?;
+                self.bind_pattern(pattern, value.clone(), None)
+                    .map_err(|err| err.with_span(span))?;
+                Ok(value)
+            }
+        }
+    }
 
-                    _ => Err("Invalid binary operation".to_string()),
+    /// `[a, ...b, , c]`, where an elision leaves a hole.
+    fn array_literal(&mut self, elements: &[Option<Expression>]) -> Result<Value, JustError> {
+        let mut values = Vec::with_capacity(elements.len());
+        for element in elements {
+            match element {
+                Some(Expression::Spread { argument, span }) => {
+                    let iterable = self.evaluate(argument)?;
+                    let iterator = iterate(iterable).map_err(|err| err.with_span(*span))?;
+                    values.extend(iterator.map(Some))
                 }
+                Some(element) => values.push(Some(self.evaluate(element)?)),
+                None => values.push(None),
             }
-            Expression::Variable(name) => match self.environment.get(name) {
-                Some(v) => Ok(v),
-                None => Ok(Value::Null),
-            },
-            Expression::Call { callee, arguments } => {
-                if let Value::Function(func_expr) = self.evaluate(&callee)? {
-                    let expr = *func_expr;
-
-                    self.enter_scope();
-
-                    for (index, arg) in arguments.iter().enumerate() {
-                        let value = self.evaluate(arg)?;
-                        if index <= arguments.len() {
-                            let var_name = &expr.parameters[index];
-                            self.environment.define(var_name.to_string(), value);
-                        } else {
-                            break;
-                        }
+        }
+
+        Ok(array::new_array_with_holes(values))
+    }
+
+    /// `{ key: value, ...source }`, inheriting from `Object.prototype`.
+    fn object_literal(&mut self, properties: &[PropertyDefinition]) -> Result<Value, JustError> {
+        let mut object = Object::with_prototype(Some(Rc::clone(&self.object_prototype)));
+        for property in properties {
+            match property {
+                PropertyDefinition::Property(key, value) => {
+                    let value = self.evaluate(value)?;
+                    object.set(key.to_string(), value);
+                }
+                PropertyDefinition::Spread(source) => {
+                    for (key, value) in own_entries(&self.evaluate(source)?) {
+                        object.set(key, value);
                     }
+                }
+            }
+        }
 
-                    let mut returned_value = Value::Null;
+        Ok(Value::Object(Rc::new(RefCell::new(object))))
+    }
 
-                    for statement in expr.body {
-                        match statement {
-                            Statement::Return(v) => {
-                                if let Some(expr) = v {
-                                    returned_value = self.evaluate(&expr)?;
-                                }
-                            }
-                            _ => self.execute(&statement)?,
-                        }
+    /// Calls `callee`. A method call `a.b()` passes `a` along as `this`.
+    fn call(
+        &mut self,
+        callee: &Expression,
+        arguments: &[Expression],
+        span: Span,
+    ) -> Result<Value, JustError> {
+        let (function, this) = match callee {
+            Expression::Member {
+                object,
+                property,
+                computed,
+                span,
+            } => {
+                let object = self.evaluate(object)?;
+                let key = self
+                    .property_key(property, *computed)
+                    .map_err(|err| err.with_span(*span))?;
+                let method = self
+                    .get_member(&object, &key)
+                    .map_err(|err| err.with_span(*span))?;
+                (method, object)
+            }
+            // `super.method()` keeps the current `this`.
+            callee @ Expression::SuperMember { .. } => {
+                (self.evaluate(callee)?, self.lookup_this()?)
+            }
+            callee => (self.evaluate(callee)?, Value::Undefined),
+        };
+
+        if !matches!(function, Value::Function(_) | Value::NativeFunction(_)) {
+            let message = format!("{} is not a function", describe(callee));
+            return Err(JustError::type_error(message).with_span(span));
+        }
+
+        let arguments = self.evaluate_arguments(arguments)?;
+        self.call_value(&function, this, arguments)
+            .map_err(|err| err.with_span(span))
+    }
+
+    /// `new callee(...arguments)`.
+    fn new_instance(
+        &mut self,
+        callee: &Expression,
+        arguments: &[Expression],
+        span: Span,
+    ) -> Result<Value, JustError> {
+        let function = match self.evaluate(callee)? {
+            Value::Function(function) if is_constructor(&function) => function,
+            _ => {
+                let message = format!("{} is not a constructor", describe(callee));
+                return Err(JustError::type_error(message).with_span(span));
+            }
+        };
+
+        let arguments = self.evaluate_arguments(arguments)?;
+        self.construct(&function, arguments)
+            .map_err(|err| err.with_span(span))
+    }
+
+    /// Reads `super.property` with the current `this` as the receiver.
+    fn super_member(
+        &mut self,
+        property: &Expression,
+        computed: bool,
+        span: Span,
+    ) -> Result<Value, JustError> {
+        let home = match self.environment.borrow().get(SUPER_BINDING) {
+            Some(home) => home,
+            None => {
+                return Err(JustError::Syntax {
+                    message: "'super' keyword unexpected here".to_string(),
+                    span: None,
+                }
+                .with_span(span))
+            }
+        };
+        let key = match self.property_key(property, computed)? {
+            PropertyKey::Public(key) => key,
+            PropertyKey::Private { .. } => unreachable!("`super.#x` is not valid syntax"),
+        };
+        let this = self.lookup_this().map_err(|err| err.with_span(span))?;
+        self.get_property(&home, &key, &this)
+            .map_err(|err| err.with_span(span))
+    }
+
+    /// Reads `this`, which a derived constructor only has once it has
+    /// called `super()`.
+    fn lookup_this(&self) -> Result<Value, JustError> {
+        self.environment
+            .borrow()
+            .lookup("this")
+            .map_err(|_| JustError::reference(UNINITIALIZED_THIS))
+    }
+
+    /// Evaluates the property of a member expression to the key it names.
+    fn property_key(
+        &mut self,
+        property: &Expression,
+        computed: bool,
+    ) -> Result<PropertyKey, JustError> {
+        match property {
+            Expression::PrivateName(name) => Ok(PropertyKey::Private {
+                name: name.to_string(),
+                key: self.private_key(name)?,
+            }),
+            Expression::Literal(Literal::String(name)) if !computed => {
+                Ok(PropertyKey::Public(name.to_string()))
+            }
+            _ => {
+                let key = self.evaluate(property)?;
+                Ok(PropertyKey::Public(to_property_key(self, &key)?))
+            }
+        }
+    }
+
+    /// Resolves `#name` to the private key of the innermost enclosing class
+    /// that declares it.
+    fn private_key(&self, name: &str) -> Result<String, JustError> {
+        match self.environment.borrow().get(name) {
+            Some(Value::String(key)) => Ok(key),
+            _ => Err(JustError::Syntax {
+                message: format!(
+                    "Private field '{}' must be declared in an enclosing class",
+                    name
+                ),
+                span: None,
+            }),
+        }
+    }
+
+    /// Reads `object[key]` or `object.#name`.
+    fn get_member(&mut self, object: &Value, key: &PropertyKey) -> Result<Value, JustError> {
+        match key {
+            PropertyKey::Public(key) => self.get_property(object, key, object),
+            PropertyKey::Private { name, key } => private_fields(object)
+                .and_then(|fields| fields.borrow().get_private(key))
+                .ok_or_else(|| {
+                    JustError::type_error(format!(
+                        "Cannot read private member {} from an object whose class did not declare it",
+                        name
+                    ))
+                }),
+        }
+    }
+
+    /// Writes `object[key] = value` or `object.#name = value`.
+    fn set_member(
+        &mut self,
+        object: &Value,
+        key: PropertyKey,
+        value: Value,
+    ) -> Result<(), JustError> {
+        match key {
+            PropertyKey::Public(key) => self.set_property(object, key, value),
+            PropertyKey::Private { name, key } => {
+                let written = private_fields(object)
+                    .is_some_and(|fields| fields.borrow_mut().set_private(&key, value));
+                if written {
+                    Ok(())
+                } else {
+                    Err(JustError::type_error(format!(
+                        "Cannot write private member {} to an object whose class did not declare it",
+                        name
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Reads `value[key]`, calling a getter with `receiver` as `this`.
+    /// Missing properties read as `null`.
+    pub(crate) fn get_property(
+        &mut self,
+        value: &Value,
+        key: &str,
+        receiver: &Value,
+    ) -> Result<Value, JustError> {
+        let object = match value {
+            Value::Object(object) => Rc::clone(object),
+            Value::Function(function) => Rc::clone(&function.object),
+            Value::Array(array) => return Ok(array::get_property(array, key)),
+            value => return get_primitive_property(value, key),
+        };
+
+        let property = object.borrow().lookup(key);
+        match property {
+            Some(Property::Data(value)) => Ok(value),
+            Some(Property::Accessor {
+                get: Some(getter), ..
+            }) => self.call_value(&getter, receiver.clone(), Vec::new()),
+            Some(Property::Accessor { get: None, .. }) | None => Ok(Value::Undefined),
+        }
+    }
+
+    /// `key in value`: whether `value` has or inherits a property called
+    /// `key`.
+    fn has_property(&mut self, key: &Value, value: &Value) -> Result<bool, JustError> {
+        let object = match value {
+            Value::Object(object) => Rc::clone(object),
+            Value::Function(function) => Rc::clone(&function.object),
+            Value::Array(array) => {
+                let key = to_property_key(self, key)?;
+                return Ok(array::has_property(array, &key));
+            }
+            _ => {
+                let searched = match value {
+                    Value::String(s) => s.to_string(),
+                    value => value.to_string(),
+                };
+                return Err(JustError::type_error(format!(
+                    "Cannot use 'in' operator to search for '{}' in {}",
+                    to_string(self, key)?,
+                    searched
+                )));
+            }
+        };
+
+        let key = to_property_key(self, key)?;
+        let found = object.borrow().lookup(&key).is_some();
+        Ok(found)
+    }
+
+    /// `value instanceof constructor`: whether `constructor.prototype` is
+    /// on `value`'s prototype chain.
+    fn instance_of(&mut self, value: &Value, constructor: &Value) -> Result<bool, JustError> {
+        let prototype = match constructor {
+            Value::Function(function) => function.object.borrow().get("prototype"),
+            Value::NativeFunction(_) => None,
+            _ => {
+                return Err(JustError::type_error(
+                    "Right-hand side of 'instanceof' is not callable",
+                ))
+            }
+        };
+        let prototype = match prototype {
+            Some(Value::Object(prototype)) => prototype,
+            prototype => {
+                return Err(JustError::type_error(format!(
+                    "Function has non-object prototype '{}' in instanceof check",
+                    prototype.unwrap_or(Value::Undefined)
+                )))
+            }
+        };
+
+        let mut current = match value {
+            Value::Object(object) => object.borrow().prototype(),
+            Value::Function(function) => function.object.borrow().prototype(),
+            _ => None,
+        };
+        while let Some(object) = current {
+            if Rc::ptr_eq(&object, &prototype) {
+                return Ok(true);
+            }
+            current = object.borrow().prototype();
+        }
+
+        Ok(false)
+    }
+
+    /// Writes `value[key] = property`, calling a setter if `value` has or
+    /// inherits one.
+    fn set_property(
+        &mut self,
+        value: &Value,
+        key: String,
+        property: Value,
+    ) -> Result<(), JustError> {
+        let object = match value {
+            Value::Object(object) => Rc::clone(object),
+            Value::Function(function) => Rc::clone(&function.object),
+            Value::Array(array) => return array::set_property(self, array, key, property),
+            value => return set_primitive_property(value, key),
+        };
+
+        let existing = object.borrow().lookup(&key);
+        match existing {
+            Some(Property::Accessor {
+                set: Some(setter), ..
+            }) => {
+                self.call_value(&setter, value.clone(), vec![property])?;
+            }
+            // A getter without a setter makes the property read-only.
+            Some(Property::Accessor { set: None, .. }) => {}
+            _ => object.borrow_mut().set(key, property),
+        }
+
+        Ok(())
+    }
+
+    fn evaluate_arguments(&mut self, arguments: &[Expression]) -> Result<Vec<Value>, JustError> {
+        let mut values = Vec::with_capacity(arguments.len());
+        for argument in arguments {
+            match argument {
+                Expression::Spread { argument, span } => {
+                    let iterable = self.evaluate(argument)?;
+                    values.extend(iterate(iterable).map_err(|err| err.with_span(*span))?)
+                }
+                argument => values.push(self.evaluate(argument)?),
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Creates a function closing over the current scope. Every function
+    /// gets its own `prototype` object, in case it is used as a constructor.
+    fn create_function(&self, parameters: Vec<Parameter>, body: Vec<Statement>) -> Value {
+        let function = Rc::new(FunctionExpression::new(
+            parameters,
+            body,
+            Rc::clone(&self.environment),
+        ));
+
+        let mut prototype = Object::with_prototype(Some(Rc::clone(&self.object_prototype)));
+        prototype.set_hidden(
+            "constructor".to_string(),
+            Value::Function(Rc::clone(&function)),
+        );
+        function.object.borrow_mut().set_hidden(
+            "prototype".to_string(),
+            Value::Object(Rc::new(RefCell::new(prototype))),
+        );
+
+        Value::Function(function)
+    }
+
+    /// Evaluates a class definition to its constructor.
+    fn define_class(&mut self, class: &Class) -> Result<Value, JustError> {
+        let parent = match &class.superclass {
+            Some(superclass) => match self.evaluate(superclass)? {
+                Value::Function(parent) if parent.object.borrow().get("prototype").is_some() => {
+                    Some(parent)
+                }
+                Value::Null => None,
+                value => {
+                    return Err(JustError::type_error(format!(
+                        "Class extends value {} is not a constructor or null",
+                        value
+                    )))
+                }
+            },
+            None => None,
+        };
+
+        // Instances inherit from the parent's prototype, or from nothing at
+        // all for `extends null`.
+        let parent_prototype = match (&parent, &class.superclass) {
+            (Some(parent), _) => match parent.object.borrow().get("prototype") {
+                Some(Value::Object(prototype)) => Some(prototype),
+                Some(Value::Null) => None,
+                _ => {
+                    return Err(JustError::type_error(
+                        "Class extends value does not have valid prototype property",
+                    ))
+                }
+            },
+            (None, Some(_)) => None,
+            (None, None) => Some(Rc::clone(&self.object_prototype)),
+        };
+        let prototype = Rc::new(RefCell::new(Object::with_prototype(
+            parent_prototype.clone(),
+        )));
+
+        // The class scope holds the class's own name and its private names,
+        // each bound to the key its fields are stored under.
+        self.class_count += 1;
+        let mut class_scope = Environment::with_parent(Rc::clone(&self.environment));
+        for member in &class.members {
+            if let ClassMember::Method {
+                key: ClassKey::Private(name),
+                ..
+            }
+            | ClassMember::Field {
+                key: ClassKey::Private(name),
+                ..
+            } = member
+            {
+                let key = format!("{}@{}", name, self.class_count);
+                class_scope.define(name.to_string(), Value::String(key));
+            }
+        }
+        let class_scope = Rc::new(RefCell::new(class_scope));
+
+        let mut instance_scope = Environment::with_parent(Rc::clone(&class_scope));
+        instance_scope.define(
+            SUPER_BINDING.to_string(),
+            parent_prototype.map_or(Value::Null, Value::Object),
+        );
+        let instance_scope = Rc::new(RefCell::new(instance_scope));
+
+        let mut static_scope = Environment::with_parent(Rc::clone(&class_scope));
+        static_scope.define(
+            SUPER_BINDING.to_string(),
+            parent.clone().map_or(Value::Null, Value::Function),
+        );
+        let static_scope = Rc::new(RefCell::new(static_scope));
+
+        // Computed keys are evaluated once, in order, while the class is
+        // being defined.
+        let outer_scope = std::mem::replace(&mut self.environment, Rc::clone(&class_scope));
+        let keys = self.class_keys(&class.members);
+        self.environment = outer_scope;
+        let keys = keys?;
+
+        let mut private_methods = Vec::new();
+        let mut fields = Vec::new();
+        for (member, key) in class.members.iter().zip(&keys) {
+            match member {
+                ClassMember::Method {
+                    is_static: false,
+                    parameters,
+                    body,
+                    ..
+                } => {
+                    if let FieldKey::Private(key) = key {
+                        let method = self.create_method(parameters, body, &instance_scope);
+                        private_methods.push((key.to_string(), method));
                     }
+                }
+                ClassMember::Field {
+                    is_static: false,
+                    initializer,
+                    ..
+                } => fields.push((key.clone(), initializer.clone())),
+                _ => {}
+            }
+        }
+
+        let (parameters, body) = class.constructor.clone().unwrap_or_default();
+        let mut constructor = FunctionExpression::new(parameters, body, Rc::clone(&instance_scope));
+        constructor.class = Some(ClassInfo {
+            name: class.name.clone(),
+            parent: parent.clone(),
+            derived: class.superclass.is_some(),
+            has_constructor: class.constructor.is_some(),
+            private_methods,
+            fields,
+        });
+        let constructor = Rc::new(constructor);
+        let constructor_value = Value::Function(Rc::clone(&constructor));
 
-                    self.exit_scope();
+        {
+            let mut object = constructor.object.borrow_mut();
+            // Static members are inherited from the parent class.
+            object.set_prototype(parent.as_ref().map(|parent| Rc::clone(&parent.object)));
+            object.set_hidden(
+                "prototype".to_string(),
+                Value::Object(Rc::clone(&prototype)),
+            );
+        }
+        prototype
+            .borrow_mut()
+            .set_hidden("constructor".to_string(), constructor_value.clone());
+        if let Some(name) = &class.name {
+            class_scope
+                .borrow_mut()
+                .define_constant(name.to_string(), constructor_value.clone());
+        }
 
-                    Ok(returned_value)
+        // Public methods and accessors go on the prototype, or on the
+        // constructor itself when static.
+        for (member, key) in class.members.iter().zip(&keys) {
+            if let ClassMember::Method {
+                kind,
+                is_static,
+                parameters,
+                body,
+                ..
+            } = member
+            {
+                let (home, scope) = if *is_static {
+                    (Rc::clone(&constructor.object), &static_scope)
                 } else {
-                    let var_name = self.evaluate(&callee)?;
-                    Err(format!("{} is not a function", var_name))
+                    (Rc::clone(&prototype), &instance_scope)
+                };
+                let method = self.create_method(parameters, body, scope);
+
+                match (key, kind) {
+                    (FieldKey::Public(key), MethodKind::Method) => {
+                        home.borrow_mut().set_hidden(key.to_string(), method)
+                    }
+                    (FieldKey::Public(key), MethodKind::Getter) => home
+                        .borrow_mut()
+                        .define_accessor(key.to_string(), Some(method), None),
+                    (FieldKey::Public(key), MethodKind::Setter) => home
+                        .borrow_mut()
+                        .define_accessor(key.to_string(), None, Some(method)),
+                    // Instance private methods were collected above.
+                    (FieldKey::Private(key), _) if *is_static => {
+                        constructor
+                            .object
+                            .borrow_mut()
+                            .define_private(key.to_string(), method);
+                    }
+                    (FieldKey::Private(_), _) => {}
                 }
             }
-            _ => Err("Expression is not implemented yet".to_string()),
         }
+
+        // Static fields and blocks run in order, with the class as `this`.
+        for (member, key) in class.members.iter().zip(&keys) {
+            match member {
+                ClassMember::Field {
+                    is_static: true,
+                    initializer,
+                    ..
+                } => {
+                    let value = match initializer {
+                        Some(initializer) => self.evaluate_in_scope(
+                            &static_scope,
+                            constructor_value.clone(),
+                            initializer,
+                        )?,
+                        None => Value::Undefined,
+                    };
+                    self.define_field(&constructor.object, key, value)?;
+                }
+                ClassMember::StaticBlock(body) => {
+                    let completion = self.in_scope(
+                        &static_scope,
+                        Some(constructor_value.clone()),
+                        |interpreter| {
+                            interpreter.hoist_var_declarations(body);
+                            interpreter.execute_statements(body)
+                        },
+                    )?;
+                    assert!(
+                        matches!(completion, Completion::Normal),
+                        "the parser rejects {:?} from a static block",
+                        completion
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        Ok(constructor_value)
+    }
+
+    /// Evaluates the key of every class member, in order. Static blocks
+    /// get a placeholder key that is never used.
+    fn class_keys(&mut self, members: &[ClassMember]) -> Result<Vec<FieldKey>, JustError> {
+        let mut keys = Vec::with_capacity(members.len());
+
+        for member in members {
+            let key = match member {
+                ClassMember::Method { key, .. } | ClassMember::Field { key, .. } => key,
+                ClassMember::StaticBlock(_) => {
+                    keys.push(FieldKey::Public(String::new()));
+                    continue;
+                }
+            };
+
+            keys.push(match key {
+                ClassKey::Named(name) => FieldKey::Public(name.to_string()),
+                ClassKey::Computed(expression) => {
+                    let key = self.evaluate(expression)?;
+                    FieldKey::Public(to_property_key(self, &key)?)
+                }
+                ClassKey::Private(name) => FieldKey::Private(self.private_key(name)?),
+            });
+        }
+
+        Ok(keys)
+    }
+
+    /// Creates a class method. Unlike other functions, methods cannot be
+    /// used as constructors, so they get no `prototype`.
+    fn create_method(
+        &self,
+        parameters: &[Parameter],
+        body: &[Statement],
+        scope: &Rc<RefCell<Environment>>,
+    ) -> Value {
+        Value::Function(Rc::new(FunctionExpression::new(
+            parameters.to_vec(),
+            body.to_vec(),
+            Rc::clone(scope),
+        )))
+    }
+
+    /// Adds a field to a freshly created instance, or to a class for a
+    /// static field.
+    fn define_field(
+        &mut self,
+        object: &Rc<RefCell<Object>>,
+        key: &FieldKey,
+        value: Value,
+    ) -> Result<(), JustError> {
+        match key {
+            FieldKey::Public(key) => object.borrow_mut().set(key.to_string(), value),
+            FieldKey::Private(key) => {
+                if !object.borrow_mut().define_private(key.to_string(), value) {
+                    let name = key.split('@').next().unwrap_or_default();
+                    return Err(JustError::type_error(format!(
+                        "Cannot initialize {} twice on the same object",
+                        name
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Installs the private methods and fields of `constructor`'s class on
+    /// the instance being constructed.
+    fn initialize_fields(
+        &mut self,
+        constructor: &FunctionExpression,
+        this: &Value,
+    ) -> Result<(), JustError> {
+        let (class, object) = match (&constructor.class, this) {
+            (Some(class), Value::Object(object)) => (class, object),
+            _ => return Ok(()),
+        };
+
+        for (key, method) in &class.private_methods {
+            self.define_field(object, &FieldKey::Private(key.to_string()), method.clone())?;
+        }
+
+        for (key, initializer) in &class.fields {
+            let value = match initializer {
+                Some(initializer) => {
+                    self.evaluate_in_scope(&constructor.closure, this.clone(), initializer)?
+                }
+                None => Value::Undefined,
+            };
+            self.define_field(object, key, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// `super(...arguments)`: runs the parent class's constructor on the
+    /// current `this`, then initializes the fields of the class it is
+    /// called in.
+    fn super_call(&mut self, arguments: Vec<Value>) -> Result<Value, JustError> {
+        let constructor = match self.environment.borrow().get(CLASS_BINDING) {
+            Some(Value::Function(constructor)) => constructor,
+            _ => {
+                return Err(JustError::Syntax {
+                    message: "'super' keyword unexpected here".to_string(),
+                    span: None,
+                })
+            }
+        };
+        let this = match self.environment.borrow().get(INSTANCE_BINDING) {
+            Some(this) => this,
+            None => unreachable!("derived constructors bind the instance they create"),
+        };
+        if self.environment.borrow().get("this").is_some() {
+            return Err(JustError::reference(
+                "Super constructor may only be called once",
+            ));
+        }
+
+        self.run_parent_constructor(&constructor, &this, arguments)?;
+        self.environment
+            .borrow_mut()
+            .initialize("this", this.clone());
+        Ok(this)
+    }
+
+    fn run_parent_constructor(
+        &mut self,
+        constructor: &Rc<FunctionExpression>,
+        this: &Value,
+        arguments: Vec<Value>,
+    ) -> Result<(), JustError> {
+        let parent = match constructor
+            .class
+            .as_ref()
+            .and_then(|class| class.parent.as_ref())
+        {
+            Some(parent) => Rc::clone(parent),
+            None => {
+                return Err(JustError::type_error(
+                    "Super constructor null of anonymous class is not a constructor",
+                ))
+            }
+        };
+
+        self.initialize(&parent, this, arguments)?;
+        self.initialize_fields(constructor, this)
+    }
+
+    /// Runs `constructor` on an object that has already been created.
+    /// Derived classes reuse the object rather than letting the parent
+    /// create it, so a parent constructor that returns a different object
+    /// does not replace `this`.
+    fn initialize(
+        &mut self,
+        constructor: &Rc<FunctionExpression>,
+        this: &Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, JustError> {
+        if let Some(class) = &constructor.class {
+            if !class.derived {
+                self.initialize_fields(constructor, this)?;
+            } else if !class.has_constructor {
+                self.run_parent_constructor(constructor, this, arguments)?;
+                return Ok(Value::Undefined);
+            }
+        }
+
+        self.call_function(constructor, this.clone(), arguments)
+    }
+
+    /// Calls `callee` with `this` as the receiver. Errors raised inside a
+    /// native function have no span, so the caller should attach one.
+    pub fn call_value(
+        &mut self,
+        callee: &Value,
+        this: Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, JustError> {
+        // Native functions may call back into scripts, e.g. `join` calling
+        // the `toString` of each element, so they count too.
+        self.stack.check()?;
+
+        match callee {
+            Value::Function(function) if function.class.is_some() => {
+                Err(JustError::type_error(format!(
+                    "Class constructor {} cannot be invoked without 'new'",
+                    function
+                        .class
+                        .as_ref()
+                        .and_then(|class| class.name.as_deref())
+                        .unwrap_or("(anonymous)")
+                )))
+            }
+            Value::Function(function) => self.call_function(function, this, arguments),
+            Value::NativeFunction(native) => (native.function)(self, this, arguments),
+            value => Err(JustError::type_error(format!(
+                "{} is not a function",
+                value
+            ))),
+        }
+    }
+
+    /// `new callee(...arguments)`: runs the constructor with a fresh object
+    /// inheriting from `callee.prototype` as `this`. The new object is the
+    /// result unless the constructor returns an object of its own.
+    fn construct(
+        &mut self,
+        function: &Rc<FunctionExpression>,
+        arguments: Vec<Value>,
+    ) -> Result<Value, JustError> {
+        let prototype = match function.object.borrow().get("prototype") {
+            Some(Value::Object(prototype)) => prototype,
+            _ => Rc::clone(&self.object_prototype),
+        };
+        let instance = Value::Object(Rc::new(RefCell::new(Object::with_prototype(Some(
+            prototype,
+        )))));
+
+        match self.initialize(function, &instance, arguments)? {
+            result @ (Value::Object(_)
+            | Value::Array(_)
+            | Value::Function(_)
+            | Value::NativeFunction(_)) => Ok(result),
+            _ => Ok(instance),
+        }
+    }
+
+    /// Runs `function`'s body in a new scope whose parent is the scope the
+    /// function closed over.
+    fn call_function(
+        &mut self,
+        function: &Rc<FunctionExpression>,
+        this: Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, JustError> {
+        // Arrow functions see the `this` and `arguments` of the scope they
+        // were created in.
+        let this = (!function.arrow).then_some(this);
+
+        let result = self.in_scope(&function.closure, this, |interpreter| {
+            let mut scope = interpreter.environment.borrow_mut();
+            if !function.arrow {
+                scope.define("arguments".to_string(), array::new_array(arguments.clone()));
+            }
+            if let Some(class) = &function.class {
+                scope.define(
+                    CLASS_BINDING.to_string(),
+                    Value::Function(Rc::clone(function)),
+                );
+                if class.derived {
+                    let this = scope.get("this").unwrap_or(Value::Undefined);
+                    scope.define(INSTANCE_BINDING.to_string(), this);
+                    scope.declare("this".to_string());
+                }
+            }
+            drop(scope);
+
+            interpreter.bind_parameters(&function.parameters, arguments)?;
+            interpreter.hoist_var_declarations(&function.body);
+            let completion = interpreter.execute_statements(&function.body)?;
+
+            match (&function.class, completion) {
+                (Some(class), completion) if class.derived => {
+                    interpreter.derived_constructor_result(completion)
+                }
+                (_, completion) => Ok(completion),
+            }
+        });
+
+        match result? {
+            Completion::Return(value) => Ok(value),
+            Completion::Normal => Ok(Value::Undefined),
+            completion => unreachable!("the parser rejects {:?} from a function", completion),
+        }
+    }
+
+    /// Checks what a derived constructor finishes with: an object to use in
+    /// place of the instance, or nothing, when `this` has to have been bound
+    /// by `super()`.
+    fn derived_constructor_result(&self, completion: Completion) -> Result<Completion, JustError> {
+        match completion {
+            Completion::Return(
+                Value::Object(_) | Value::Array(_) | Value::Function(_) | Value::NativeFunction(_),
+            ) => Ok(completion),
+            Completion::Return(Value::Undefined) | Completion::Normal => {
+                Ok(Completion::Return(self.lookup_this()?))
+            }
+            _ => Err(JustError::type_error(
+                "Derived constructors may only return object or undefined",
+            )),
+        }
+    }
+
+    /// Binds each parameter to its argument in the current scope. Missing
+    /// arguments take the parameter's default, evaluated after the
+    /// parameters before it are bound, and a rest parameter collects
+    /// whatever arguments are left over.
+    fn bind_parameters(
+        &mut self,
+        parameters: &[Parameter],
+        arguments: Vec<Value>,
+    ) -> Result<(), JustError> {
+        let mut arguments = arguments.into_iter();
+
+        for parameter in parameters {
+            let value = if parameter.rest {
+                // The rest parameter is always last, so nothing else wants
+                // the remaining arguments.
+                array::new_array(arguments.by_ref().collect())
+            } else {
+                match (arguments.next(), &parameter.default) {
+                    (Some(Value::Undefined) | None, Some(default)) => self.evaluate(default)?,
+                    (Some(argument), _) => argument,
+                    (None, None) => Value::Undefined,
+                }
+            };
+
+            // Parameters are bound in the function's own scope, like `let`.
+            self.bind_pattern(&parameter.pattern, value, Some(DeclarationKind::Let))?;
+        }
+
+        Ok(())
+    }
+
+    /// Binds `value` to the targets in `pattern`, taking arrays and objects
+    /// apart on the way. A declaration defines its variables in the current
+    /// scope, while an assignment (no `declaration`) updates existing
+    /// variables and members.
+    fn bind_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value: Value,
+        declaration: Option<DeclarationKind>,
+    ) -> Result<(), JustError> {
+        match pattern {
+            Pattern::Identifier(name) => {
+                let mut scope = self.environment.borrow_mut();
+                match declaration {
+                    // A `var` was hoisted to the function or script scope,
+                    // which may be outside the current block.
+                    None | Some(DeclarationKind::Var) => {
+                        return scope.assign(name.to_string(), value)
+                    }
+                    Some(DeclarationKind::Const) => scope.define_constant(name.to_string(), value),
+                    Some(_) => scope.define(name.to_string(), value),
+                }
+                Ok(())
+            }
+            Pattern::Member(Expression::Member {
+                object,
+                property,
+                computed,
+                span,
+            }) => {
+                let object = self.evaluate(object)?;
+                let key = self
+                    .property_key(property, *computed)
+                    .map_err(|err| err.with_span(*span))?;
+                self.set_member(&object, key, value)
+                    .map_err(|err| err.with_span(*span))
+            }
+            Pattern::Member(_) => unreachable!("the parser only produces member targets"),
+            // Errors binding the elements that know no better place point
+            // at the innermost pattern.
+            Pattern::Array {
+                elements,
+                rest,
+                span,
+            } => self
+                .bind_array_pattern(elements, rest.as_deref(), value, declaration)
+                .map_err(|err| err.with_span(*span)),
+            Pattern::Object {
+                properties,
+                rest,
+                span,
+            } => self
+                .bind_object_pattern(properties, rest.as_deref(), value, declaration)
+                .map_err(|err| err.with_span(*span)),
+        }
+    }
+
+    fn bind_array_pattern(
+        &mut self,
+        elements: &[Option<PatternElement>],
+        rest: Option<&Pattern>,
+        value: Value,
+        declaration: Option<DeclarationKind>,
+    ) -> Result<(), JustError> {
+        let mut values = iterate(value)?;
+        for element in elements {
+            // A hole skips a value too.
+            let value = values.next().unwrap_or(Value::Undefined);
+            if let Some(element) = element {
+                self.bind_element(element, value, declaration)?;
+            }
+        }
+
+        match rest {
+            Some(rest) => self.bind_pattern(rest, array::new_array(values.collect()), declaration),
+            None => Ok(()),
+        }
+    }
+
+    fn bind_object_pattern(
+        &mut self,
+        properties: &[PatternProperty],
+        rest: Option<&Pattern>,
+        value: Value,
+        declaration: Option<DeclarationKind>,
+    ) -> Result<(), JustError> {
+        if let Value::Null | Value::Undefined = value {
+            return Err(JustError::type_error(format!(
+                "Cannot destructure '{0}' as it is {0}.",
+                value
+            )));
+        }
+
+        let mut used = Vec::new();
+        for property in properties {
+            let key = self.property_key(&property.key, property.computed)?;
+            let field = self.get_member(&value, &key)?;
+            if let PropertyKey::Public(key) = key {
+                used.push(key);
+            }
+            self.bind_element(&property.value, field, declaration)?;
+        }
+
+        let rest = match rest {
+            Some(rest) => rest,
+            None => return Ok(()),
+        };
+        let mut object = Object::with_prototype(Some(Rc::clone(&self.object_prototype)));
+        for (key, field) in own_entries(&value) {
+            if !used.contains(&key) {
+                object.set(key, field);
+            }
+        }
+        self.bind_pattern(
+            rest,
+            Value::Object(Rc::new(RefCell::new(object))),
+            declaration,
+        )
+    }
+
+    /// Binds one element of an array or object pattern, falling back to
+    /// its default when the value is missing.
+    fn bind_element(
+        &mut self,
+        element: &PatternElement,
+        value: Value,
+        declaration: Option<DeclarationKind>,
+    ) -> Result<(), JustError> {
+        let value = match (value, &element.default) {
+            (Value::Undefined, Some(default)) => self.evaluate(default)?,
+            (value, _) => value,
+        };
+
+        self.bind_pattern(&element.target, value, declaration)
+    }
+
+    /// The value a compound assignment like `a += b` or `a &&= b` stores,
+    /// given the target's current value, or `None` if it short-circuits and
+    /// neither evaluates `value` nor stores anything.
+    fn compound_assignment(
+        &mut self,
+        operator: &TokenType,
+        current: &Value,
+        value: &Expression,
+    ) -> Result<Option<Value>, JustError> {
+        let logical = match operator {
+            TokenType::AndEqual => Some(TokenType::And),
+            TokenType::OrEqual => Some(TokenType::Or),
+            TokenType::QuestionQuestionEqual => Some(TokenType::QuestionQuestion),
+            _ => None,
+        };
+        if let Some(logical) = logical {
+            if short_circuits(&logical, current) {
+                return Ok(None);
+            }
+            return self.evaluate(value).map(Some);
+        }
+
+        let operator = match operator {
+            TokenType::PlusEqual => TokenType::Plus,
+            TokenType::MinusEqual => TokenType::Minus,
+            TokenType::StarEqual => TokenType::Star,
+            TokenType::SlashEqual => TokenType::Slash,
+            TokenType::PercentEqual => TokenType::Percent,
+            TokenType::PowerEqual => TokenType::Power,
+            TokenType::LesserLesserEqual => TokenType::LesserLesser,
+            TokenType::GreaterGreaterEqual => TokenType::GreaterGreater,
+            TokenType::GreaterGreaterGreaterEqual => TokenType::GreaterGreaterGreater,
+            TokenType::AmpersandEqual => TokenType::Ampersand,
+            TokenType::PipeEqual => TokenType::Pipe,
+            TokenType::CaretEqual => TokenType::Caret,
+            _ => unreachable!("the parser only produces assignment operators here"),
+        };
+        let value = self.evaluate(value)?;
+        self.binary_operation(&operator, current, &value).map(Some)
+    }
+
+    /// Applies a binary operator. Equality and relational operators give
+    /// booleans, `+` concatenates when either operand converts to a string,
+    /// and every other operator works on numbers, the bitwise ones on their
+    /// 32-bit integer forms.
+    fn binary_operation(
+        &mut self,
+        operator: &TokenType,
+        left: &Value,
+        right: &Value,
+    ) -> Result<Value, JustError> {
+        if *operator == TokenType::Plus {
+            let left = to_primitive(self, left, Hint::Default)?;
+            let right = to_primitive(self, right, Hint::Default)?;
+
+            if let (Value::String(_), _) | (_, Value::String(_)) = (&left, &right) {
+                let concatenated = to_string(self, &left)? + &to_string(self, &right)?;
+                return Ok(Value::String(concatenated));
+            }
+
+            let sum = to_numeric(self, &left)? + to_numeric(self, &right)?;
+            return Ok(Value::Number(sum));
+        }
+
+        let comparison = match operator {
+            TokenType::In => Some(self.has_property(left, right)?),
+            TokenType::Instanceof => Some(self.instance_of(left, right)?),
+            TokenType::EqualEqual => Some(is_loosely_equal(self, left, right)?),
+            TokenType::BangEqual => Some(!is_loosely_equal(self, left, right)?),
+            TokenType::EqualEqualEqual => Some(left.strict_equals(right)),
+            TokenType::BangEqualEqual => Some(!left.strict_equals(right)),
+            // `a > b` is `b < a`, and `a <= b` is `!(b < a)`, but either is
+            // false when an operand is NaN.
+            TokenType::Lesser => Some(is_less_than(self, left, right, true)? == Some(true)),
+            TokenType::Greater => Some(is_less_than(self, right, left, false)? == Some(true)),
+            TokenType::LesserEqual => Some(is_less_than(self, right, left, false)? == Some(false)),
+            TokenType::GreaterEqual => Some(is_less_than(self, left, right, true)? == Some(false)),
+            _ => None,
+        };
+        if let Some(comparison) = comparison {
+            return Ok(Value::Boolean(comparison));
+        }
+
+        let (a, b) = match operator {
+            TokenType::Minus
+            | TokenType::Star
+            | TokenType::Slash
+            | TokenType::Percent
+            | TokenType::Power
+            | TokenType::LesserLesser
+            | TokenType::GreaterGreater
+            | TokenType::GreaterGreaterGreater
+            | TokenType::Ampersand
+            | TokenType::Pipe
+            | TokenType::Caret => (to_numeric(self, left)?, to_numeric(self, right)?),
+            _ => return Err(JustError::type_error("Invalid binary operation")),
+        };
+
+        // Shifts only use the low five bits of the count, so `1 << 33` is 2.
+        let shift = to_uint32(b) & 0x1f;
+        Ok(Value::Number(match operator {
+            TokenType::Minus => a - b,
+            TokenType::Star => a * b,
+            TokenType::Slash => a / b,
+            TokenType::Percent => a % b,
+            TokenType::Power => exponentiate(a, b),
+            TokenType::LesserLesser => f64::from(to_int32(a) << shift),
+            TokenType::GreaterGreater => f64::from(to_int32(a) >> shift),
+            TokenType::GreaterGreaterGreater => f64::from(to_uint32(a) >> shift),
+            TokenType::Ampersand => f64::from(to_int32(a) & to_int32(b)),
+            TokenType::Pipe => f64::from(to_int32(a) | to_int32(b)),
+            _ => f64::from(to_int32(a) ^ to_int32(b)),
+        }))
+    }
+
+    fn evaluate_in_scope(
+        &mut self,
+        parent: &Rc<RefCell<Environment>>,
+        this: Value,
+        expression: &Expression,
+    ) -> Result<Value, JustError> {
+        self.in_scope(parent, Some(this), |interpreter| {
+            interpreter.evaluate(expression)
+        })
+    }
+
+    /// Runs `run` in a new scope below `parent`, binding `this` if given,
+    /// then restores the current scope.
+    fn in_scope<T>(
+        &mut self,
+        parent: &Rc<RefCell<Environment>>,
+        this: Option<Value>,
+        run: impl FnOnce(&mut Self) -> Result<T, JustError>,
+    ) -> Result<T, JustError> {
+        let mut scope = Environment::with_parent(Rc::clone(parent));
+        if let Some(this) = this {
+            scope.define("this".to_string(), this);
+        }
+        let outer_scope = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(scope)));
+
+        let result = run(self);
+        self.environment = outer_scope;
+        result
     }
 
     fn enter_scope(&mut self) {
-        let new_env = Environment::with_parent(self.environment.clone());
-        self.environment = new_env;
+        let new_env = Environment::with_parent(Rc::clone(&self.environment));
+        self.environment = Rc::new(RefCell::new(new_env));
     }
 
     fn exit_scope(&mut self) {
-        if let Some(parent) = self.environment.parent.take() {
-            self.environment = *parent;
+        let parent = self.environment.borrow().parent.clone();
+        if let Some(parent) = parent {
+            self.environment = parent;
         } else {
             panic!("Cannot exit from the global scope")
         }
     }
 }
 
-fn is_truthy(value: &Value) -> bool {
+/// LoopContinues: whether a loop with label set `labels` keeps iterating
+/// after its body produced `completion`.
+fn loop_continues(completion: &Completion, labels: &[String]) -> bool {
+    match completion {
+        Completion::Normal | Completion::Continue(None) => true,
+        Completion::Continue(Some(label)) => labels.contains(label),
+        _ => false,
+    }
+}
+
+/// The values a `for...of` loop or a spread `...value` iterates over. An
+/// array is read one index at a time against its live length, so elements
+/// added while a loop runs are visited too, and holes read as `undefined`.
+fn iterate(value: Value) -> Result<Box<dyn Iterator<Item = Value>>, JustError> {
+    match value {
+        Value::String(s) => {
+            let characters: Vec<_> = s.chars().map(|c| Value::String(c.to_string())).collect();
+            Ok(Box::new(characters.into_iter()))
+        }
+        Value::Array(array) => {
+            let mut index = 0;
+            Ok(Box::new(std::iter::from_fn(move || {
+                let array = array.borrow();
+                let element = array.elements.get(index)?;
+                index += 1;
+                Some(element.clone().unwrap_or(Value::Undefined))
+            })))
+        }
+        value => Err(JustError::type_error(format!("{} is not iterable", value))),
+    }
+}
+
+/// The own enumerable properties of `value`, as copied by an object spread
+/// `{ ...value }`. Spreading `null` or a primitive copies nothing.
+fn own_entries(value: &Value) -> Vec<(String, Value)> {
+    let object = match value {
+        Value::Object(object) => Rc::clone(object),
+        Value::Function(function) => Rc::clone(&function.object),
+        Value::Array(array) => {
+            return array::keys(array)
+                .into_iter()
+                .map(|key| {
+                    let value = array::get_property(array, &key);
+                    (key, value)
+                })
+                .collect()
+        }
+        Value::String(s) => {
+            return s
+                .encode_utf16()
+                .enumerate()
+                .map(|(index, unit)| {
+                    let unit = String::from_utf16_lossy(&[unit]);
+                    (index.to_string(), Value::String(unit))
+                })
+                .collect()
+        }
+        _ => return Vec::new(),
+    };
+
+    let object = object.borrow();
+    object
+        .keys()
+        .into_iter()
+        .filter_map(|key| object.get(&key).map(|value| (key, value)))
+        .collect()
+}
+
+/// Whether the logical operator `operator` (`&&`, `||` or `??`) already
+/// has its result in `left`, so its right operand is not evaluated.
+fn short_circuits(operator: &TokenType, left: &Value) -> bool {
+    match operator {
+        TokenType::And => !to_boolean(left),
+        TokenType::Or => to_boolean(left),
+        _ => !matches!(left, Value::Null | Value::Undefined),
+    }
+}
+
+/// Whether `function` can be called with `new`. Arrow functions and class
+/// methods cannot, since they have no `prototype`.
+fn is_constructor(function: &FunctionExpression) -> bool {
+    !function.arrow && function.object.borrow().get("prototype").is_some()
+}
+
+/// Spells out a callee the way error messages refer to it, e.g.
+/// `obj.method`, `fns[0]` or `makeAdder(...)`.
+fn describe(expression: &Expression) -> String {
+    match expression {
+        Expression::Variable { name, .. } => name.to_string(),
+        Expression::This(_) => "this".to_string(),
+        Expression::Grouping(inner) => describe(inner),
+        Expression::Member {
+            object,
+            property,
+            computed,
+            ..
+        } => {
+            let object = describe(object);
+            match (property.as_ref(), computed) {
+                (Expression::Literal(Literal::String(name)), false) => {
+                    format!("{}.{}", object, name)
+                }
+                (Expression::PrivateName(name), _) => format!("{}.{}", object, name),
+                (Expression::Literal(Literal::String(key)), true) => {
+                    format!("{}[\"{}\"]", object, key)
+                }
+                (Expression::Literal(Literal::Number(index)), true) => {
+                    format!("{}[{}]", object, index)
+                }
+                (Expression::Variable { name, .. }, true) => format!("{}[{}]", object, name),
+                _ => format!("{}[...]", object),
+            }
+        }
+        Expression::SuperMember {
+            property,
+            computed: false,
+            ..
+        } => match property.as_ref() {
+            Expression::Literal(Literal::String(name)) => format!("super.{}", name),
+            _ => "super[...]".to_string(),
+        },
+        Expression::Call { callee, .. } => format!("{}(...)", describe(callee)),
+        _ => "(intermediate value)".to_string(),
+    }
+}
+
+/// The object holding `value`'s private fields, if it can have any.
+fn private_fields(value: &Value) -> Option<Rc<RefCell<Object>>> {
+    match value {
+        Value::Object(object) => Some(Rc::clone(object)),
+        Value::Function(function) => Some(Rc::clone(&function.object)),
+        _ => None,
+    }
+}
+
+/// Reads `value[key]` for values without an `Object` of their own: strings,
+/// `null` and `undefined`. Missing properties read as `undefined`.
+fn get_primitive_property(value: &Value, key: &str) -> Result<Value, JustError> {
     match value {
-        Value::Number(v) => *v > 0.0,
-        Value::String(v) => v.clone().len() > 0,
-        Value::Boolean(b) => *b,
-        Value::Null => false,
-        Value::Function(_) => true,
+        Value::String(s) => {
+            if key == "length" {
+                return Ok(Value::Number(s.encode_utf16().count() as f64));
+            }
+
+            let unit = array_index(key).and_then(|index| s.encode_utf16().nth(index as usize));
+            Ok(match unit {
+                Some(unit) => Value::String(String::from_utf16_lossy(&[unit])),
+                None => Value::Undefined,
+            })
+        }
+        Value::Null | Value::Undefined => Err(JustError::type_error(format!(
+            "Cannot read properties of {} (reading '{}')",
+            value, key
+        ))),
+        _ => Ok(Value::Undefined),
+    }
+}
+
+/// Writes `value[key]` for primitives. Properties set on primitives other
+/// than `null` and `undefined` are silently dropped, as in sloppy-mode
+/// JavaScript.
+fn set_primitive_property(value: &Value, key: String) -> Result<(), JustError> {
+    match value {
+        Value::Null | Value::Undefined => Err(JustError::type_error(format!(
+            "Cannot set properties of {} (setting '{}')",
+            value, key
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{assert_bool, assert_error, assert_number, assert_string, error};
+
+    #[test]
+    fn operators_convert_their_operands() {
+        let strings = [
+            (r#""a" + 1"#, "a1"),
+            (r#"1 + "2""#, "12"),
+            (r#""1" + null"#, "1null"),
+            ("[1, [2, 3]] + \"\"", "1,2,3"),
+            ("[] + {}", "[object Object]"),
+            (r#"({ toString: () => "s" }) + "!""#, "s!"),
+        ];
+        for (source, expected) in strings {
+            assert_string(source, expected);
+        }
+
+        let numbers = [
+            (r#""3" * 2"#, 6.0),
+            ("true + 1", 2.0),
+            ("null + 1", 1.0),
+            ("undefined + 1", f64::NAN),
+            ("[] + 1 - 1", 0.0),
+            ("+[7]", 7.0),
+            ("+[1, 2]", f64::NAN),
+            ("({ valueOf: () => 42 }) * 2", 84.0),
+            ("-\"\"", -0.0),
+            ("1 / 0", f64::INFINITY),
+            ("1 ** (0 / 0)", f64::NAN),
+            ("(-1) ** (1 / 0)", f64::NAN),
+        ];
+        for (source, expected) in numbers {
+            assert_number(source, expected);
+        }
+    }
+
+    #[test]
+    fn equality_operators() {
+        let cases = [
+            ("null == undefined", true),
+            ("null === undefined", false),
+            ("0 / 0 != 0 / 0", true),
+            ("0 / 0 !== 0 / 0", true),
+            ("0 === -0", true),
+            (r#"1 === "1""#, false),
+            (r#"1 !== "1""#, true),
+            ("let o = {}; let p = o; o === p", true),
+        ];
+        for (source, expected) in cases {
+            assert_bool(source, expected);
+        }
+    }
+
+    #[test]
+    fn relational_operators() {
+        let cases = [
+            (r#""10" < "9""#, true),
+            ("0 / 0 < 1", false),
+            ("0 / 0 >= 0 / 0", false),
+            ("0 / 0 <= 0 / 0", false),
+            ("null >= 0", true),
+            ("undefined >= 0", false),
+            ("2 > 1", true),
+            ("1 <= 1", true),
+        ];
+        for (source, expected) in cases {
+            assert_bool(source, expected);
+        }
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        let cases = [
+            ("5 & 3", 1.0),
+            ("5 | 3", 7.0),
+            ("5 ^ 3", 6.0),
+            ("~5", -6.0),
+            ("~~-3.7", -3.0),
+            ("1 << 31", -2147483648.0),
+            ("1 << 33", 2.0),
+            ("-64 >> 2", -16.0),
+            ("-1 >>> 0", 4294967295.0),
+            ("-1 >>> 28", 15.0),
+            (r#""12" & "10""#, 8.0),
+            ("1 | 2 ^ 3 & 4", 3.0),
+            ("1 << 2 + 1", 8.0),
+            ("let x = 6; x &= 3; x <<= 2; x", 8.0),
+        ];
+        for (source, expected) in cases {
+            assert_number(source, expected);
+        }
+    }
+
+    #[test]
+    fn var_is_hoisted_to_the_function_or_script() {
+        let cases = [
+            ("const before = x; var x = 1; before === undefined", true),
+            ("{ var y = 1 } y === 1", true),
+            (
+                "function f(c) { if (c) { var z = 3 } return z } f(true) === 3 && f(false) === undefined",
+                true,
+            ),
+            ("for (var i = 0; i < 3; i++) {} i === 3", true),
+            ("function g(p) { var p; return p } g(5) === 5", true),
+            ("var a = 1; var a; a === 1", true),
+        ];
+        for (source, expected) in cases {
+            assert_bool(source, expected);
+        }
+    }
+
+    #[test]
+    fn update_expressions() {
+        let cases = [
+            ("let i = 1; i++ + ++i", 4.0),
+            ("let i = 1; i-- - --i", 2.0),
+            ("let o = { n: 1 }; o.n++; o.n", 2.0),
+            (r#"let s = "5"; s++"#, 5.0),
+        ];
+        for (source, expected) in cases {
+            assert_number(source, expected);
+        }
+    }
+
+    #[test]
+    fn runaway_recursion_is_a_range_error() {
+        assert_number(
+            "function f(n) { return n == 0 ? 0 : 1 + f(n - 1) } f(20)",
+            20.0,
+        );
+
+        let nested = "let a = []; for (let i = 0; i < 20000; i++) a = [a];";
+        let sources = [
+            "function f() { return f() } f()".to_string(),
+            "function f(n) { return n == 0 ? 0 : 1 + f(n - 1) } f(100000)".to_string(),
+            r#"const o = { toString: function () { return this + "" } }; o + """#.to_string(),
+            format!("{} a.join()", nested),
+            format!(r#"{} a + """#, nested),
+            format!("{} a.flat(1 / 0)", nested),
+        ];
+        for source in &sources {
+            assert_error(source, "RangeError: Maximum call stack size exceeded");
+        }
+    }
+
+    #[test]
+    fn deeply_nested_values_are_freed_without_recursing() {
+        assert_number(
+            "let list = null; for (let i = 0; i < 100000; i++) list = { next: list }; 1",
+            1.0,
+        );
+        assert_number(
+            "let a = []; for (let i = 0; i < 100000; i++) a = [a]; 1",
+            1.0,
+        );
+    }
+
+    #[test]
+    fn derived_constructors_bind_this_with_super() {
+        let parent = "class A { constructor(x) { this.x = x } }";
+        let cases = [
+            (
+                "class B extends A { constructor() { super(1); this.y = 2 } } new B().y === 2",
+                true,
+            ),
+            (
+                "class B extends A { constructor() { (() => super(3))() } } new B().x === 3",
+                true,
+            ),
+            (
+                "class B extends A { constructor() { return { z: 1 } } } new B().z === 1",
+                true,
+            ),
+            ("class B extends A {} new B(4).x === 4", true),
+            ("class B extends A {} Object.getPrototypeOf(B) === A", true),
+            (
+                "Object.getPrototypeOf(A) === Object.getPrototypeOf(Object)",
+                true,
+            ),
+        ];
+        for (source, expected) in cases {
+            assert_bool(&format!("{} {}", parent, source), expected);
+        }
+
+        let uninitialized = "ReferenceError: Must call super constructor in derived class before accessing 'this' or returning from derived constructor";
+        let errors = [
+            (
+                "class B extends A { constructor() { this.y = 1; super() } } new B()",
+                uninitialized,
+            ),
+            (
+                "class B extends A { constructor() { super.x; super() } } new B()",
+                uninitialized,
+            ),
+            (
+                "class B extends A { constructor() {} } new B()",
+                uninitialized,
+            ),
+            (
+                "class B extends A { constructor() { super(); super() } } new B()",
+                "ReferenceError: Super constructor may only be called once",
+            ),
+            (
+                "class B extends A { constructor() { super(); return 1 } } new B()",
+                "TypeError: Derived constructors may only return object or undefined",
+            ),
+            (
+                "class B extends null {} new B()",
+                "TypeError: Super constructor null of anonymous class is not a constructor",
+            ),
+        ];
+        for (source, expected) in errors {
+            assert_error(&format!("{} {}", parent, source), expected);
+        }
+    }
+
+    #[test]
+    fn in_instanceof_typeof_and_delete() {
+        let prelude = "class A {} class B extends A {} const o = { a: 1 }; const arr = [1, , 3];";
+        let cases = [
+            (r#""a" in o"#, true),
+            (r#""toString" in o"#, true),
+            (r#""b" in o"#, false),
+            ("0 in arr", true),
+            ("1 in arr", false),
+            (r#""length" in arr && "push" in arr"#, true),
+            ("new B() instanceof A", true),
+            ("new A() instanceof B", false),
+            ("1 instanceof A", false),
+            (r#"typeof undeclared === "undefined""#, true),
+            (
+                r#"typeof null === "object" && typeof A === "function""#,
+                true,
+            ),
+            (r#"delete o.a && !("a" in o)"#, true),
+            ("delete arr[0] && !(0 in arr) && arr.length === 3", true),
+            ("delete arr.length", false),
+            ("delete o", false),
+        ];
+        for (source, expected) in cases {
+            assert_bool(&format!("{} {}", prelude, source), expected);
+        }
+
+        let errors = [
+            (
+                "1 in 5",
+                "TypeError: Cannot use 'in' operator to search for '1' in 5",
+            ),
+            (
+                "({}) instanceof {}",
+                "TypeError: Right-hand side of 'instanceof' is not callable",
+            ),
+            (
+                "typeof y; let y = 1",
+                "ReferenceError: Cannot access 'y' before initialization",
+            ),
+            (
+                "class C { #x; m() { delete this.#x } }",
+                "SyntaxError: Private fields can not be deleted",
+            ),
+        ];
+        for (source, expected) in errors {
+            assert_error(source, expected);
+        }
+    }
+
+    #[test]
+    fn destructuring_and_spread_errors_point_at_the_pattern() {
+        let cases = [
+            ("const {a} = null", "{a}"),
+            ("let [x] = 5", "[x]"),
+            ("function f() {} f(...5)", "...5"),
+            ("[1, ...null]", "...null"),
+            ("let y; ({ y: [y] } = { y: 3 })", "[y]"),
+            ("function g({ a }) {} g()", "{ a }"),
+        ];
+        for (source, spanned) in cases {
+            let error = error(source);
+            assert_eq!(error.name(), "TypeError", "{}", source);
+            let span = error.span().expect("the error has a span");
+            assert_eq!(&source[span.start_pos..span.end_pos], spanned, "{}", source);
+        }
     }
 }
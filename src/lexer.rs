@@ -1,3 +1,5 @@
+use crate::error::{JustError, Span};
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
     // Literals
@@ -6,6 +8,7 @@ pub enum TokenType {
     Boolean(bool),
     Null,
     Identifier(String),
+    PrivateName(String), // `#name`, including the `#`
 
     // Keywords
     Let,
@@ -16,30 +19,68 @@ pub enum TokenType {
     If,
     Else,
     For,
+    In,
+    Instanceof,
     While,
     Break,
     Continue,
     Throw,
     Typeof,
+    Void,
+    Delete,
+    New,
+    This,
+    Class,
+    Extends,
+    Super,
+    Static,
 
     // Operators
     Plus,
+    PlusPlus,  // ++
+    PlusEqual, // +=
     Minus,
+    MinusMinus, // --
+    MinusEqual, // -=
     Star,
+    StarEqual, // *=
     Slash,
+    SlashEqual, // /=
     Percent,
+    PercentEqual, // %=
     Power,
+    PowerEqual, // **=
+    Ampersand,
+    AmpersandEqual, // &=
+    Pipe,
+    PipeEqual, // |=
+    Caret,
+    CaretEqual, // ^=
+    Tilde,
     Equal,
     EqualEqual,
+    EqualEqualEqual, // ===
+    Arrow,           // =>
     BangEqual,
+    BangEqualEqual, // !==
     Bang,
     And,
+    AndEqual, // &&=
     Or,
+    OrEqual, // ||=
     Question,
+    QuestionQuestion,      // ??
+    QuestionQuestionEqual, // ??=
     Lesser,
     LesserEqual,
+    LesserLesser,      // <<
+    LesserLesserEqual, // <<=
     Greater,
     GreaterEqual,
+    GreaterGreater,             // >>
+    GreaterGreaterEqual,        // >>=
+    GreaterGreaterGreater,      // >>>
+    GreaterGreaterGreaterEqual, // >>>=
 
     // Punctuation
     LParen,   // (
@@ -50,6 +91,7 @@ pub enum TokenType {
     RBracket, // ]
     Comma,
     Dot,
+    Ellipsis, // ...
     SemiColon,
     Colon,
 
@@ -72,9 +114,10 @@ pub struct Token {
 pub struct Lexer {
     source: String,
     tokens: Vec<Token>,
+    errors: Vec<JustError>,
     line: usize,
+    line_start: usize,
     current: usize,
-    col: usize,
     start: usize,
 }
 
@@ -83,17 +126,19 @@ impl Lexer {
         Self {
             source,
             tokens: vec![],
+            errors: vec![],
             line: 1,
+            line_start: 0,
             current: 0,
             start: 0,
-            col: 1,
         }
     }
 
-    pub fn tokenize(&mut self) -> Vec<Token> {
-        while !self.is_at_end() {
+    /// Splits the source into tokens. Lexing carries on past bad input so
+    /// that every lexical error in the source is reported at once.
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, Vec<JustError>> {
+        while let Some(c) = self.peek() {
             self.start = self.current;
-            let c = self.peek().unwrap();
             self.advance();
 
             match c {
@@ -103,87 +148,166 @@ impl Lexer {
                 '}' => self.add_token(TokenType::RBrace),
                 '[' => self.add_token(TokenType::LBracket),
                 ']' => self.add_token(TokenType::RBracket),
-                '.' => self.add_token(TokenType::Dot),
+                '.' => {
+                    if self.source[self.current..].starts_with("..") {
+                        self.advance();
+                        self.advance();
+                        self.add_token(TokenType::Ellipsis);
+                    } else {
+                        self.add_token(TokenType::Dot);
+                    }
+                }
                 ',' => self.add_token(TokenType::Comma),
                 ';' => self.add_token(TokenType::SemiColon),
                 ':' => self.add_token(TokenType::Colon),
-                '+' => self.add_token(TokenType::Plus),
-                '-' => self.add_token(TokenType::Minus),
+                '+' => {
+                    if self.match_char('+') {
+                        self.add_token(TokenType::PlusPlus);
+                    } else if self.match_char('=') {
+                        self.add_token(TokenType::PlusEqual);
+                    } else {
+                        self.add_token(TokenType::Plus);
+                    }
+                }
+                '-' => {
+                    if self.match_char('-') {
+                        self.add_token(TokenType::MinusMinus);
+                    } else if self.match_char('=') {
+                        self.add_token(TokenType::MinusEqual);
+                    } else {
+                        self.add_token(TokenType::Minus);
+                    }
+                }
                 '*' => {
-                    match self.peek() {
-                        Some('*') => {
-                            self.advance();
+                    if self.match_char('*') {
+                        if self.match_char('=') {
+                            self.add_token(TokenType::PowerEqual);
+                        } else {
                             self.add_token(TokenType::Power);
                         }
-                        Some(_) => self.add_token(TokenType::Star),
-                        None => {}
-                    };
+                    } else if self.match_char('=') {
+                        self.add_token(TokenType::StarEqual);
+                    } else {
+                        self.add_token(TokenType::Star);
+                    }
                 }
                 '/' => {
-                    match self.peek() {
-                        Some('/') => self.comment(),
-                        Some(_) => {
-                            self.add_token(TokenType::Slash);
-                        }
-                        None => {}
-                    };
+                    if self.peek() == Some('/') {
+                        self.comment();
+                    } else if self.match_char('=') {
+                        self.add_token(TokenType::SlashEqual);
+                    } else {
+                        self.add_token(TokenType::Slash);
+                    }
                 }
-                '%' => self.add_token(TokenType::Percent),
-                '=' => match self.peek() {
-                    Some('=') => {
-                        self.advance();
-                        self.add_token(TokenType::EqualEqual);
+                '%' => {
+                    if self.match_char('=') {
+                        self.add_token(TokenType::PercentEqual);
+                    } else {
+                        self.add_token(TokenType::Percent);
                     }
-                    Some(_) => self.add_token(TokenType::Equal),
-                    None => {}
-                },
-                '!' => match self.peek() {
-                    Some('=') => {
-                        self.advance();
-                        self.add_token(TokenType::BangEqual);
+                }
+                '=' => {
+                    if self.match_char('=') {
+                        if self.match_char('=') {
+                            self.add_token(TokenType::EqualEqualEqual);
+                        } else {
+                            self.add_token(TokenType::EqualEqual);
+                        }
+                    } else if self.match_char('>') {
+                        self.add_token(TokenType::Arrow);
+                    } else {
+                        self.add_token(TokenType::Equal);
                     }
-                    Some(_) => {
+                }
+                '!' => {
+                    if self.match_char('=') {
+                        if self.match_char('=') {
+                            self.add_token(TokenType::BangEqualEqual);
+                        } else {
+                            self.add_token(TokenType::BangEqual);
+                        }
+                    } else {
                         self.add_token(TokenType::Bang);
                     }
-                    None => {}
-                },
-                '&' => match self.peek() {
-                    Some('&') => {
-                        self.advance();
-                        self.add_token(TokenType::And);
+                }
+                '&' => {
+                    if self.match_char('&') {
+                        if self.match_char('=') {
+                            self.add_token(TokenType::AndEqual);
+                        } else {
+                            self.add_token(TokenType::And);
+                        }
+                    } else if self.match_char('=') {
+                        self.add_token(TokenType::AmpersandEqual);
+                    } else {
+                        self.add_token(TokenType::Ampersand);
                     }
-                    Some(_) => {}
-                    None => {}
-                },
-                '|' => match self.peek() {
-                    Some('|') => {
-                        self.advance();
-                        self.add_token(TokenType::Or);
+                }
+                '|' => {
+                    if self.match_char('|') {
+                        if self.match_char('=') {
+                            self.add_token(TokenType::OrEqual);
+                        } else {
+                            self.add_token(TokenType::Or);
+                        }
+                    } else if self.match_char('=') {
+                        self.add_token(TokenType::PipeEqual);
+                    } else {
+                        self.add_token(TokenType::Pipe);
                     }
-                    Some(_) => {}
-                    None => {}
-                },
-                '?' => self.add_token(TokenType::Question),
-                '<' => match self.peek() {
-                    Some('=') => {
-                        self.advance();
-                        self.add_token(TokenType::LesserEqual);
+                }
+                '^' => {
+                    if self.match_char('=') {
+                        self.add_token(TokenType::CaretEqual);
+                    } else {
+                        self.add_token(TokenType::Caret);
+                    }
+                }
+                '~' => self.add_token(TokenType::Tilde),
+                '?' => {
+                    if self.match_char('?') {
+                        if self.match_char('=') {
+                            self.add_token(TokenType::QuestionQuestionEqual);
+                        } else {
+                            self.add_token(TokenType::QuestionQuestion);
+                        }
+                    } else {
+                        self.add_token(TokenType::Question);
                     }
-                    Some(_) => {
+                }
+                '<' => {
+                    if self.match_char('<') {
+                        if self.match_char('=') {
+                            self.add_token(TokenType::LesserLesserEqual);
+                        } else {
+                            self.add_token(TokenType::LesserLesser);
+                        }
+                    } else if self.match_char('=') {
+                        self.add_token(TokenType::LesserEqual);
+                    } else {
                         self.add_token(TokenType::Lesser);
                     }
-                    None => {}
-                },
-                '>' => match self.peek() {
-                    Some('=') => {
-                        self.advance();
+                }
+                '>' => {
+                    if self.match_char('>') {
+                        if self.match_char('>') {
+                            if self.match_char('=') {
+                                self.add_token(TokenType::GreaterGreaterGreaterEqual);
+                            } else {
+                                self.add_token(TokenType::GreaterGreaterGreater);
+                            }
+                        } else if self.match_char('=') {
+                            self.add_token(TokenType::GreaterGreaterEqual);
+                        } else {
+                            self.add_token(TokenType::GreaterGreater);
+                        }
+                    } else if self.match_char('=') {
                         self.add_token(TokenType::GreaterEqual);
-                    }
-                    Some(_) => {
+                    } else {
                         self.add_token(TokenType::Greater);
                     }
-                    None => {}
-                },
+                }
 
                 // Whitespace and newlines
                 ' ' | '\r' | '\t' => {
@@ -199,26 +323,21 @@ impl Lexer {
                 '"' => self.string(),
                 // Identifiers and keywords
                 'a'..='z' | 'A'..='Z' | '_' => self.identifier(),
+                '#' => self.private_name(),
 
                 // Unknown character
-                _ => {
-                    panic!(
-                        "Unexpected character: {} at line {} at col {}",
-                        c, self.line, self.col
-                    );
-                }
+                _ => self.error(format!("Unexpected character: {}", c)),
             }
-
-            self.col += 1;
         }
 
+        self.start = self.current;
         self.add_token(TokenType::Eof);
 
-        self.tokens.clone()
-    }
-
-    fn is_at_end(&self) -> bool {
-        self.current >= self.source.len()
+        if self.errors.is_empty() {
+            Ok(self.tokens.clone())
+        } else {
+            Err(self.errors.clone())
+        }
     }
 
     fn add_token(&mut self, ttype: TokenType) {
@@ -226,24 +345,53 @@ impl Lexer {
         self.tokens.push(Token {
             ttype,
             lexeme,
-            col: self.col,
+            col: self.start_col(),
             line: self.line,
             start_pos: self.start,
             end_pos: self.current,
         })
     }
 
+    /// Records a lexical error covering the text of the current token.
+    fn error(&mut self, message: String) {
+        let span = Span {
+            line: self.line,
+            col: self.start_col(),
+            start_pos: self.start,
+            end_pos: self.current,
+        };
+
+        self.errors.push(JustError::lexical(message, span));
+    }
+
+    /// The 1-based column, in characters, the current token starts at.
+    fn start_col(&self) -> usize {
+        self.source[self.line_start..self.start].chars().count() + 1
+    }
+
     fn peek(&self) -> Option<char> {
-        self.source.chars().nth(self.current)
+        self.source[self.current..].chars().next()
     }
 
     fn advance(&mut self) {
-        self.current += 1;
+        if let Some(c) = self.peek() {
+            self.current += c.len_utf8();
+        }
+    }
+
+    /// Consumes the next character if it is `expected`.
+    fn match_char(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            true
+        } else {
+            false
+        }
     }
 
     fn advance_line(&mut self) {
         self.line += 1;
-        self.col = 1;
+        self.line_start = self.current;
     }
 
     fn number(&mut self) {
@@ -262,25 +410,28 @@ impl Lexer {
                 self.add_token(TokenType::Number(v));
             }
             Err(_) => {
-                panic!(
+                let message = format!(
                     "Failed to parse number {}",
-                    self.source[self.start..self.current].to_string()
+                    &self.source[self.start..self.current]
                 );
+                self.error(message);
             }
         }
     }
 
     fn string(&mut self) {
         while let Some(c) = self.peek() {
-            if c == '"' || self.is_at_end() {
+            if c == '"' || c == '\n' {
                 break;
             }
 
             self.advance();
         }
 
-        if self.is_at_end() {
-            panic!("Unterminated string")
+        // Strings cannot span lines, so lexing resumes on the next line.
+        if self.peek() != Some('"') {
+            self.error("Unterminated string".to_string());
+            return;
         }
 
         // Consume closing "
@@ -309,11 +460,21 @@ impl Lexer {
             "if" => self.add_token(TokenType::If),
             "else" => self.add_token(TokenType::Else),
             "for" => self.add_token(TokenType::For),
+            "in" => self.add_token(TokenType::In),
+            "instanceof" => self.add_token(TokenType::Instanceof),
             "while" => self.add_token(TokenType::While),
             "break" => self.add_token(TokenType::Break),
             "continue" => self.add_token(TokenType::Continue),
             "throw" => self.add_token(TokenType::Throw),
             "typeof" => self.add_token(TokenType::Typeof),
+            "void" => self.add_token(TokenType::Void),
+            "delete" => self.add_token(TokenType::Delete),
+            "new" => self.add_token(TokenType::New),
+            "this" => self.add_token(TokenType::This),
+            "class" => self.add_token(TokenType::Class),
+            "extends" => self.add_token(TokenType::Extends),
+            "super" => self.add_token(TokenType::Super),
+            "static" => self.add_token(TokenType::Static),
             "null" => self.add_token(TokenType::Null),
             "true" => self.add_token(TokenType::Boolean(true)),
             "false" => self.add_token(TokenType::Boolean(false)),
@@ -321,6 +482,27 @@ impl Lexer {
         }
     }
 
+    fn private_name(&mut self) {
+        match self.peek() {
+            Some(c) if c.is_alphabetic() || c == '_' => {}
+            _ => {
+                self.error("Invalid or unexpected token".to_string());
+                return;
+            }
+        }
+
+        while let Some(c) = self.peek() {
+            if !c.is_alphanumeric() && c != '_' {
+                break;
+            }
+
+            self.advance();
+        }
+
+        let name = self.source[self.start..self.current].to_string();
+        self.add_token(TokenType::PrivateName(name));
+    }
+
     fn comment(&mut self) {
         while let Some(c) = self.peek() {
             if c == '\n' {
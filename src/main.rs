@@ -1,15 +1,27 @@
+use std::{env, fs, process, thread};
+
+use diagnostics::Diagnostic;
+use environment::Value;
+use error::JustError;
 use interpreter::Interpreter;
 use lexer::Lexer;
 use parser::Parser;
 
+mod array;
 mod ast;
+mod diagnostics;
 mod environment;
+mod error;
 mod interpreter;
 mod lexer;
+mod object;
+mod operations;
 mod parser;
+mod stack;
+#[cfg(test)]
+mod testing;
 
-fn main() {
-    let source = r#"
+const DEMO_SOURCE: &str = r#"
     function pow(a, b) {
         return a ** b
     }
@@ -19,23 +31,88 @@ fn main() {
     }
         
     add(pow(2, 3), 2);
-    "#
-    .to_string();
-    let mut lexer = Lexer::new(source);
-    let tokens = lexer.tokenize();
-    let mut parser = Parser::new(tokens);
-    let ast = parser.parse().unwrap();
-    let mut interpreter = Interpreter::new();
-
-    // println!("{:#?}", ast);
-
-    match interpreter.interpret(ast) {
-        Ok(v) => match v {
-            Some(v) => println!("{}", v),
-            None => {}
+    "#;
+
+/// The stack scripts run on, which bounds how deeply they can recurse. The
+/// main thread's is far smaller.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Usage: `just [--json] [--color] [script.js]`
+///
+/// Runs the given script (or a small demo without one) and prints the value
+/// of its last expression. Errors are rendered with a source snippet, or as
+/// one JSON object per line with `--json`.
+fn main() {
+    let cli = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(cli)
+        .expect("the interpreter thread can be spawned");
+
+    // A panic has already been reported by the thread that raised it.
+    if cli.join().is_err() {
+        process::exit(101);
+    }
+}
+
+fn cli() {
+    let mut json = false;
+    let mut color = false;
+    let mut path = None;
+
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--color" => color = true,
+            _ => path = Some(arg),
+        }
+    }
+
+    let source = match &path {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("Cannot read {}: {}", path, err);
+                process::exit(1);
+            }
         },
-        Err(err) => {
-            eprintln!("Error: {}", err);
+        None => DEMO_SOURCE.to_string(),
+    };
+
+    match run(source.clone(), STACK_SIZE) {
+        Ok(v) => {
+            if let Some(v) = v {
+                println!("{}", v)
+            }
+        }
+        Err(errors) => {
+            for err in &errors {
+                let mut diagnostic = Diagnostic::new(err, &source);
+                if let Some(path) = &path {
+                    diagnostic = diagnostic.with_path(path);
+                }
+
+                if json {
+                    eprintln!("{}", diagnostic.to_json());
+                } else {
+                    eprint!("{}", diagnostic.render(color));
+                }
+            }
+            process::exit(1);
         }
     }
 }
+
+/// Runs `source` on the current thread, which has `stack_size` bytes of
+/// stack.
+fn run(source: String, stack_size: usize) -> Result<Option<Value>, Vec<JustError>> {
+    let tokens = Lexer::new(source).tokenize()?;
+    let (ast, errors) = Parser::new(tokens).with_stack_size(stack_size).parse();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Interpreter::new()
+        .with_stack_size(stack_size)
+        .interpret(ast)
+        .map_err(|err| vec![err])
+}
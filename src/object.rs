@@ -0,0 +1,400 @@
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use crate::{
+    environment::{drop_values, NativeFn, NativeFunction, Value},
+    error::JustError,
+    interpreter::Interpreter,
+};
+
+/// A JavaScript object: a bag of own properties that remembers the order
+/// they were added in, plus the `[[Prototype]]` it inherits from.
+#[derive(Clone, Default)]
+pub struct Object {
+    properties: HashMap<String, Property>,
+    insertion_order: Vec<String>,
+    prototype: Option<Rc<RefCell<Object>>>,
+    /// Private fields and methods. Keys combine the `#name` with the class
+    /// that declared it, so two classes' `#x` never clash.
+    private: HashMap<String, Value>,
+}
+
+/// An own property: a plain value, or the getter and setter that stand in
+/// for one.
+#[derive(Debug, Clone)]
+pub enum Property {
+    Data(Value),
+    Accessor {
+        get: Option<Value>,
+        set: Option<Value>,
+    },
+}
+
+impl Object {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_prototype(prototype: Option<Rc<RefCell<Object>>>) -> Self {
+        let mut object = Self::default();
+        object.prototype = prototype;
+        object
+    }
+
+    /// Looks `key` up on the object, then along its prototype chain.
+    pub fn lookup(&self, key: &str) -> Option<Property> {
+        match self.properties.get(key) {
+            Some(property) => Some(property.clone()),
+            None => self.prototype.as_ref()?.borrow().lookup(key),
+        }
+    }
+
+    /// Looks up a plain value. Accessors read as absent here, since calling
+    /// a getter is up to the interpreter.
+    pub fn get(&self, key: &str) -> Option<Value> {
+        match self.lookup(key)? {
+            Property::Data(value) => Some(value),
+            Property::Accessor { .. } => None,
+        }
+    }
+
+    pub fn set(&mut self, key: String, value: Value) {
+        if !self.properties.contains_key(&key) {
+            self.insertion_order.push(key.clone());
+        }
+
+        self.properties.insert(key, Property::Data(value));
+    }
+
+    /// Sets a property that is skipped by `keys`, like the `constructor`
+    /// of a function's `prototype` object.
+    pub fn set_hidden(&mut self, key: String, value: Value) {
+        self.properties.insert(key, Property::Data(value));
+    }
+
+    /// Defines the getter or setter of a hidden accessor property, keeping
+    /// the other half if the property already has one.
+    pub fn define_accessor(&mut self, key: String, get: Option<Value>, set: Option<Value>) {
+        let (get, set) = match self.properties.remove(&key) {
+            Some(Property::Accessor {
+                get: old_get,
+                set: old_set,
+            }) => (get.or(old_get), set.or(old_set)),
+            _ => (get, set),
+        };
+
+        self.properties.insert(key, Property::Accessor { get, set });
+    }
+
+    /// Removes an own property, hidden or not. Inherited properties are
+    /// left alone.
+    pub fn delete(&mut self, key: &str) {
+        if self.properties.remove(key).is_some() {
+            self.insertion_order.retain(|existing| existing != key);
+        }
+    }
+
+    pub fn get_private(&self, key: &str) -> Option<Value> {
+        self.private.get(key).cloned()
+    }
+
+    /// Adds a private field. Returns `false` if the object already has it,
+    /// which happens when a constructor initializes the same object twice.
+    pub fn define_private(&mut self, key: String, value: Value) -> bool {
+        if self.private.contains_key(&key) {
+            return false;
+        }
+
+        self.private.insert(key, value);
+        true
+    }
+
+    /// Updates an existing private field. Returns `false` if the object has
+    /// no such field.
+    pub fn set_private(&mut self, key: &str, value: Value) -> bool {
+        match self.private.get_mut(key) {
+            Some(field) => {
+                *field = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn prototype(&self) -> Option<Rc<RefCell<Object>>> {
+        self.prototype.clone()
+    }
+
+    pub fn set_prototype(&mut self, prototype: Option<Rc<RefCell<Object>>>) {
+        self.prototype = prototype;
+    }
+
+    /// Empties the object, handing back every value it holds: property
+    /// values, accessors, private fields and its prototype.
+    pub fn take_values(&mut self) -> Vec<Value> {
+        self.insertion_order.clear();
+        let properties = self
+            .properties
+            .drain()
+            .flat_map(|(_, property)| match property {
+                Property::Data(value) => vec![value],
+                Property::Accessor { get, set } => get.into_iter().chain(set).collect(),
+            });
+
+        let mut values: Vec<_> = properties.collect();
+        values.extend(self.private.drain().map(|(_, value)| value));
+        values.extend(self.prototype.take().map(Value::Object));
+        values
+    }
+
+    /// The own property keys in the order ECMAScript enumerates them:
+    /// array indices in ascending order, then every other key in the order
+    /// it was added.
+    pub fn keys(&self) -> Vec<String> {
+        let mut indices: Vec<(u32, &String)> = self
+            .insertion_order
+            .iter()
+            .filter_map(|key| array_index(key).map(|index| (index, key)))
+            .collect();
+        indices.sort_by_key(|(index, _)| *index);
+
+        indices
+            .into_iter()
+            .map(|(_, key)| key.to_string())
+            .chain(
+                self.insertion_order
+                    .iter()
+                    .filter(|key| array_index(key).is_none())
+                    .cloned(),
+            )
+            .collect()
+    }
+}
+
+/// Every enumerable key a `for...in` loop visits: the object's own keys,
+/// then those of each prototype that are not shadowed by an earlier one.
+pub fn enumerable_keys(object: &Rc<RefCell<Object>>) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut shadowed: Vec<String> = Vec::new();
+    let mut current = Some(Rc::clone(object));
+
+    while let Some(object) = current {
+        let object = object.borrow();
+        for key in object.keys() {
+            if !shadowed.contains(&key) {
+                keys.push(key);
+            }
+        }
+        shadowed.extend(object.properties.keys().cloned());
+        current = object.prototype();
+    }
+
+    keys
+}
+
+// Objects can contain themselves, so only the keys are printed.
+impl Drop for Object {
+    fn drop(&mut self) {
+        drop_values(self.take_values());
+    }
+}
+
+impl fmt::Debug for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.keys()).finish()
+    }
+}
+
+/// Parses `key` as an array index: the canonical decimal form of an integer
+/// below 2^32 - 1.
+pub fn array_index(key: &str) -> Option<u32> {
+    if key.is_empty() || (key.len() > 1 && key.starts_with('0')) {
+        return None;
+    }
+
+    if !key.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    key.parse::<u32>().ok().filter(|index| *index != u32::MAX)
+}
+
+/// Builds `Object.prototype`, which every object literal inherits from.
+pub fn prototype() -> Rc<RefCell<Object>> {
+    let methods: [(&str, NativeFn); 2] = [("toString", to_string), ("valueOf", value_of)];
+
+    let mut prototype = Object::new();
+    for (name, function) in methods {
+        prototype.set_hidden(
+            name.to_string(),
+            Value::NativeFunction(NativeFunction { name, function }),
+        );
+    }
+
+    Rc::new(RefCell::new(prototype))
+}
+
+/// Builds the global `Object`, holding the prototype every object literal
+/// inherits from and the static methods for working with prototypes.
+pub fn constructor(object_prototype: &Rc<RefCell<Object>>) -> Value {
+    let statics: [(&str, NativeFn); 4] = [
+        ("create", create),
+        ("getPrototypeOf", get_prototype_of),
+        ("is", is),
+        ("setPrototypeOf", set_prototype_of),
+    ];
+
+    let mut object = Object::new();
+    object.set_hidden(
+        "prototype".to_string(),
+        Value::Object(Rc::clone(object_prototype)),
+    );
+    for (name, function) in statics {
+        object.set_hidden(
+            name.to_string(),
+            Value::NativeFunction(NativeFunction { name, function }),
+        );
+    }
+
+    Value::Object(Rc::new(RefCell::new(object)))
+}
+
+/// Checks a value passed as a new `[[Prototype]]`: an object, or `null` for
+/// no prototype at all.
+fn prototype_argument(value: Option<&Value>) -> Result<Option<Rc<RefCell<Object>>>, JustError> {
+    match value {
+        Some(Value::Object(prototype)) => Ok(Some(Rc::clone(prototype))),
+        Some(Value::Null) => Ok(None),
+        value => Err(JustError::type_error(format!(
+            "Object prototype may only be an Object or null: {}",
+            value.unwrap_or(&Value::Undefined)
+        ))),
+    }
+}
+
+/// `Object.prototype.toString`, which names the kind of value it is called
+/// on, e.g. `[object Array]`.
+fn to_string(_: &mut Interpreter, this: Value, _: Vec<Value>) -> Result<Value, JustError> {
+    let tag = match this {
+        Value::Undefined => "Undefined",
+        Value::Null => "Null",
+        Value::Array(_) => "Array",
+        Value::Function(_) | Value::NativeFunction(_) => "Function",
+        Value::String(_) => "String",
+        Value::Number(_) => "Number",
+        Value::Boolean(_) => "Boolean",
+        Value::Object(_) => "Object",
+    };
+
+    Ok(Value::String(format!("[object {}]", tag)))
+}
+
+fn value_of(_: &mut Interpreter, this: Value, _: Vec<Value>) -> Result<Value, JustError> {
+    match this {
+        Value::Null | Value::Undefined => Err(JustError::type_error(
+            "Cannot convert undefined or null to object",
+        )),
+        this => Ok(this),
+    }
+}
+
+fn create(_: &mut Interpreter, _: Value, arguments: Vec<Value>) -> Result<Value, JustError> {
+    let prototype = prototype_argument(arguments.first())?;
+    let object = Object::with_prototype(prototype);
+
+    Ok(Value::Object(Rc::new(RefCell::new(object))))
+}
+
+fn get_prototype_of(
+    _: &mut Interpreter,
+    _: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    match arguments.first() {
+        Some(Value::Object(object)) => Ok(object
+            .borrow()
+            .prototype()
+            .map_or(Value::Null, Value::Object)),
+        Some(Value::Function(function)) => {
+            let prototype = function.object.borrow().prototype();
+            let parent = function
+                .class
+                .as_ref()
+                .and_then(|class| class.parent.as_ref());
+            // A derived class inherits from its parent's properties, which
+            // are not the parent itself as a value.
+            match (prototype, parent) {
+                (Some(prototype), Some(parent)) if Rc::ptr_eq(&prototype, &parent.object) => {
+                    Ok(Value::Function(Rc::clone(parent)))
+                }
+                (prototype, _) => Ok(prototype.map_or(Value::Null, Value::Object)),
+            }
+        }
+        Some(Value::Null | Value::Undefined) | None => Err(JustError::type_error(
+            "Cannot convert undefined or null to object",
+        )),
+        Some(_) => Ok(Value::Null),
+    }
+}
+
+/// `Object.is`, which tells values apart by SameValue: unlike `===`, it
+/// finds `NaN` equal to itself and `0` different from `-0`.
+fn is(_: &mut Interpreter, _: Value, arguments: Vec<Value>) -> Result<Value, JustError> {
+    let mut arguments = arguments.into_iter();
+    let x = arguments.next().unwrap_or(Value::Undefined);
+    let y = arguments.next().unwrap_or(Value::Undefined);
+
+    Ok(Value::Boolean(x.same_value(&y)))
+}
+
+fn set_prototype_of(
+    _: &mut Interpreter,
+    _: Value,
+    arguments: Vec<Value>,
+) -> Result<Value, JustError> {
+    let target = arguments.first().cloned().unwrap_or(Value::Undefined);
+    let prototype = prototype_argument(arguments.get(1))?;
+
+    let object = match &target {
+        Value::Object(object) => Rc::clone(object),
+        Value::Function(function) => Rc::clone(&function.object),
+        Value::Null | Value::Undefined => {
+            return Err(JustError::type_error(
+                "Object.setPrototypeOf called on null or undefined",
+            ))
+        }
+        // Primitives have no [[Prototype]] slot to change.
+        _ => return Ok(target),
+    };
+
+    // Refuse to close a loop in the chain, which would make lookups spin.
+    let mut current = prototype.clone();
+    while let Some(ancestor) = current {
+        if Rc::ptr_eq(&ancestor, &object) {
+            return Err(JustError::type_error("Cyclic __proto__ value"));
+        }
+        current = ancestor.borrow().prototype();
+    }
+
+    object.borrow_mut().set_prototype(prototype);
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::assert_bool;
+
+    #[test]
+    fn object_is_uses_same_value() {
+        let cases = [
+            ("Object.is(0, -0)", false),
+            ("Object.is(-0, -0)", true),
+            ("Object.is(0 / 0, 0 / 0)", true),
+            (r#"Object.is("a", "a")"#, true),
+            ("Object.is({}, {})", false),
+            ("Object.is()", true),
+        ];
+        for (source, expected) in cases {
+            assert_bool(source, expected);
+        }
+    }
+}
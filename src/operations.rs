@@ -0,0 +1,584 @@
+use crate::{
+    environment::{ClassInfo, Value},
+    error::JustError,
+    interpreter::Interpreter,
+};
+
+// The abstract operations ECMAScript uses to convert between types, which
+// give the operators their implicit coercions, e.g. `"3" * 2` or `[] + 1`.
+
+/// The type ToPrimitive should prefer for an object that could be either.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hint {
+    Default,
+    Number,
+    String,
+}
+
+/// ToPrimitive: primitives are returned as they are, while objects are
+/// asked for one through their `valueOf` and `toString` methods.
+pub fn to_primitive(
+    interpreter: &mut Interpreter,
+    value: &Value,
+    hint: Hint,
+) -> Result<Value, JustError> {
+    if is_primitive(value) {
+        return Ok(value.clone());
+    }
+
+    // OrdinaryToPrimitive: `valueOf` before `toString`, except when a
+    // string is preferred.
+    let methods = match hint {
+        Hint::String => ["toString", "valueOf"],
+        Hint::Default | Hint::Number => ["valueOf", "toString"],
+    };
+    for name in methods {
+        let method = interpreter.get_property(value, name, value)?;
+        if let Value::Function(_) | Value::NativeFunction(_) = method {
+            let result = interpreter.call_value(&method, value.clone(), Vec::new())?;
+            if is_primitive(&result) {
+                return Ok(result);
+            }
+        }
+    }
+
+    // Functions have no `toString` of their own to find. Function source
+    // text is not kept, so they print the way built-in functions do.
+    match value {
+        Value::Function(function) => {
+            let name = match &function.class {
+                Some(ClassInfo {
+                    name: Some(name), ..
+                }) => name,
+                _ => "",
+            };
+            Ok(Value::String(native_source(name)))
+        }
+        Value::NativeFunction(native) => Ok(Value::String(native_source(native.name))),
+        _ => Err(JustError::type_error(
+            "Cannot convert object to primitive value",
+        )),
+    }
+}
+
+fn native_source(name: &str) -> String {
+    format!("function {}() {{ [native code] }}", name)
+}
+
+pub fn is_primitive(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Number(_) | Value::String(_) | Value::Boolean(_) | Value::Null | Value::Undefined
+    )
+}
+
+/// What the `typeof` operator says `value` is. `null` is an `"object"`.
+pub fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Boolean(_) => "boolean",
+        Value::Undefined => "undefined",
+        Value::Null | Value::Object(_) | Value::Array(_) => "object",
+        Value::Function(_) | Value::NativeFunction(_) => "function",
+    }
+}
+
+/// ToBoolean: `false`, `0`, `-0`, `NaN`, `""`, `null` and `undefined` are
+/// falsy, and everything else, every object included, is truthy.
+pub fn to_boolean(value: &Value) -> bool {
+    match value {
+        Value::Number(n) => !(*n == 0.0 || n.is_nan()),
+        Value::String(s) => !s.is_empty(),
+        Value::Boolean(b) => *b,
+        Value::Null | Value::Undefined => false,
+        Value::Function(_) | Value::NativeFunction(_) | Value::Object(_) | Value::Array(_) => true,
+    }
+}
+
+/// ToNumber, e.g. `"12"` → 12, `""` → 0, `true` → 1, `null` → 0 and
+/// `undefined` → NaN.
+pub fn to_number(interpreter: &mut Interpreter, value: &Value) -> Result<f64, JustError> {
+    Ok(match value {
+        Value::Number(n) => *n,
+        Value::String(s) => string_to_number(s),
+        Value::Boolean(b) => f64::from(u8::from(*b)),
+        Value::Null => 0.0,
+        Value::Undefined => f64::NAN,
+        value => {
+            let primitive = to_primitive(interpreter, value, Hint::Number)?;
+            to_number(interpreter, &primitive)?
+        }
+    })
+}
+
+/// ToNumeric, the operand conversion of the arithmetic operators. Without
+/// BigInt every numeric value is a Number, so this is ToNumber.
+pub fn to_numeric(interpreter: &mut Interpreter, value: &Value) -> Result<f64, JustError> {
+    to_number(interpreter, value)
+}
+
+/// ToInt32 of a value already converted to a number: `n` wrapped into the
+/// signed 32-bit range the bitwise operators work in, e.g. `2 ** 32 + 5` →
+/// 5 and `2 ** 31` → `-(2 ** 31)`. NaN and the infinities become 0.
+pub fn to_int32(n: f64) -> i32 {
+    to_uint32(n) as i32
+}
+
+/// ToUint32 of a value already converted to a number: `n` wrapped into the
+/// unsigned 32-bit range, e.g. `-1` → `2 ** 32 - 1`.
+pub fn to_uint32(n: f64) -> u32 {
+    if !n.is_finite() {
+        return 0;
+    }
+
+    n.trunc().rem_euclid(4294967296.0) as u32
+}
+
+/// ToString, e.g. `1e21` → `"1e+21"`, `null` → `"null"` and `[1, [2]]` →
+/// `"1,2"`.
+pub fn to_string(interpreter: &mut Interpreter, value: &Value) -> Result<String, JustError> {
+    Ok(match value {
+        Value::String(s) => s.to_string(),
+        Value::Number(n) => number_to_string(*n),
+        Value::Boolean(b) => b.to_string(),
+        Value::Null => "null".to_string(),
+        Value::Undefined => "undefined".to_string(),
+        value => {
+            let primitive = to_primitive(interpreter, value, Hint::String)?;
+            to_string(interpreter, &primitive)?
+        }
+    })
+}
+
+/// ToPropertyKey: the string key a value names when used as a property,
+/// e.g. the `1` in `obj[1]`.
+pub fn to_property_key(interpreter: &mut Interpreter, value: &Value) -> Result<String, JustError> {
+    let key = to_primitive(interpreter, value, Hint::String)?;
+    to_string(interpreter, &key)
+}
+
+/// IsLooselyEqual, the `==` operator: values of different types are
+/// converted towards numbers until they can be compared strictly, except
+/// that `null` and `undefined` only equal each other.
+pub fn is_loosely_equal(
+    interpreter: &mut Interpreter,
+    x: &Value,
+    y: &Value,
+) -> Result<bool, JustError> {
+    Ok(match (x, y) {
+        (Value::Null | Value::Undefined, Value::Null | Value::Undefined) => true,
+        (Value::Number(n), Value::String(s)) | (Value::String(s), Value::Number(n)) => {
+            *n == string_to_number(s)
+        }
+        (Value::Boolean(b), other) | (other, Value::Boolean(b)) => {
+            let number = Value::Number(f64::from(u8::from(*b)));
+            is_loosely_equal(interpreter, &number, other)?
+        }
+        (primitive @ (Value::Number(_) | Value::String(_)), object)
+        | (object, primitive @ (Value::Number(_) | Value::String(_)))
+            if !is_primitive(object) =>
+        {
+            let object = to_primitive(interpreter, object, Hint::Default)?;
+            is_loosely_equal(interpreter, primitive, &object)?
+        }
+        _ => x.strict_equals(y),
+    })
+}
+
+/// IsLessThan, which the relational operators are built on. It is `None`
+/// when either side is NaN, making every comparison with it false. The
+/// operands are converted in source order, so `left_first` says whether
+/// `x` is the left one.
+pub fn is_less_than(
+    interpreter: &mut Interpreter,
+    x: &Value,
+    y: &Value,
+    left_first: bool,
+) -> Result<Option<bool>, JustError> {
+    let (x, y) = if left_first {
+        let x = to_primitive(interpreter, x, Hint::Number)?;
+        (x, to_primitive(interpreter, y, Hint::Number)?)
+    } else {
+        let y = to_primitive(interpreter, y, Hint::Number)?;
+        (to_primitive(interpreter, x, Hint::Number)?, y)
+    };
+
+    // Strings compare by UTF-16 code units, so `"Z" < "a"` and `"10" < "9"`.
+    if let (Value::String(x), Value::String(y)) = (&x, &y) {
+        return Ok(Some(x.encode_utf16().lt(y.encode_utf16())));
+    }
+
+    let x = to_numeric(interpreter, &x)?;
+    let y = to_numeric(interpreter, &y)?;
+    if x.is_nan() || y.is_nan() {
+        Ok(None)
+    } else {
+        Ok(Some(x < y))
+    }
+}
+
+/// StringToNumber: the numeric literal `s` spells, ignoring whitespace
+/// around it, or NaN if it spells none. An empty string is 0.
+fn string_to_number(s: &str) -> f64 {
+    let s = s.trim_matches(is_whitespace);
+    if s.is_empty() {
+        return 0.0;
+    }
+
+    let radix = match s.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0o" | "0O") => 8,
+        Some("0b" | "0B") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        let digits = &s[2..];
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return f64::NAN;
+        }
+
+        return digits.chars().fold(0.0, |n, c| {
+            n * f64::from(radix) + f64::from(c.to_digit(radix).unwrap_or(0))
+        });
+    }
+
+    let unsigned = s.strip_prefix(['+', '-']).unwrap_or(s);
+    if unsigned == "Infinity" {
+        return if s.starts_with('-') {
+            f64::NEG_INFINITY
+        } else {
+            f64::INFINITY
+        };
+    }
+
+    // Rust accepts more than JavaScript does, like `inf` and `NaN`, so the
+    // syntax is checked first.
+    if is_decimal_literal(unsigned) {
+        s.parse().unwrap_or(f64::NAN)
+    } else {
+        f64::NAN
+    }
+}
+
+/// Whether `s` is an unsigned decimal literal: digits with an optional
+/// fraction and exponent, e.g. `12`, `1.`, `.5` or `2.5e-3`.
+fn is_decimal_literal(s: &str) -> bool {
+    let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+
+    let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => {
+            let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            (mantissa, Some(exponent))
+        }
+        None => (s, None),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    !(integer.is_empty() && fraction.is_empty())
+        && digits(integer)
+        && digits(fraction)
+        && exponent.is_none_or(|exponent| !exponent.is_empty() && digits(exponent))
+}
+
+/// The StrWhiteSpaceChar set: whitespace and line terminators.
+fn is_whitespace(c: char) -> bool {
+    matches!(
+        c,
+        '\t' | '\n' | '\u{b}' | '\u{c}' | '\r' | ' ' | '\u{a0}' | '\u{1680}' | '\u{2000}'
+            ..='\u{200a}'
+                | '\u{2028}'
+                | '\u{2029}'
+                | '\u{202f}'
+                | '\u{205f}'
+                | '\u{3000}'
+                | '\u{feff}'
+    )
+}
+
+/// Number::toString: the shortest digits that read back as `n`, written
+/// out in full for magnitudes from 1e-6 up to 1e21 and in exponential
+/// notation outside them.
+pub fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        return "NaN".to_string();
+    }
+    if n == 0.0 {
+        return "0".to_string();
+    }
+    if n < 0.0 {
+        return format!("-{}", number_to_string(-n));
+    }
+    if n.is_infinite() {
+        return "Infinity".to_string();
+    }
+
+    // `{:e}` also picks the shortest round-tripping digits, e.g. `1.2345e3`,
+    // so `n` is `digits` × 10^(point - digits.len()).
+    let scientific = format!("{:e}", n);
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("exponential notation has an exponent");
+    let digits = mantissa.replace('.', "");
+    let length = digits.len() as i32;
+    let point = exponent.parse::<i32>().expect("the exponent is an integer") + 1;
+
+    if length <= point && point <= 21 {
+        digits + &"0".repeat((point - length) as usize)
+    } else if 0 < point && point <= 21 {
+        let (integer, fraction) = digits.split_at(point as usize);
+        format!("{}.{}", integer, fraction)
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else {
+        let exponent = point - 1;
+        let sign = if exponent < 0 { '-' } else { '+' };
+        let (first, rest) = digits.split_at(1);
+
+        if rest.is_empty() {
+            format!("{}e{}{}", first, sign, exponent.abs())
+        } else {
+            format!("{}.{}e{}{}", first, rest, sign, exponent.abs())
+        }
+    }
+}
+
+/// Number::exponentiate, the `**` operator. It differs from `powf` where
+/// IEEE 754 and ECMAScript disagree: `1 ** NaN` and `(-1) ** Infinity` are
+/// NaN rather than 1.
+pub fn exponentiate(base: f64, exponent: f64) -> f64 {
+    if exponent.is_nan() || (base.abs() == 1.0 && exponent.is_infinite()) {
+        return f64::NAN;
+    }
+
+    base.powf(exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{evaluate, same_number};
+
+    #[test]
+    fn string_to_number_reads_numeric_literals() {
+        let cases = [
+            ("12", 12.0),
+            ("  12\n\t", 12.0),
+            ("", 0.0),
+            ("   ", 0.0),
+            ("-0", -0.0),
+            ("+1.5", 1.5),
+            (".5", 0.5),
+            ("5.", 5.0),
+            ("1e3", 1000.0),
+            ("2.5E-3", 0.0025),
+            ("0x1F", 31.0),
+            ("0o17", 15.0),
+            ("0b101", 5.0),
+            ("Infinity", f64::INFINITY),
+            ("-Infinity", f64::NEG_INFINITY),
+            ("-0x10", f64::NAN),
+            ("0x", f64::NAN),
+            ("1_000", f64::NAN),
+            ("1e", f64::NAN),
+            (".", f64::NAN),
+            ("inf", f64::NAN),
+            ("NaN", f64::NAN),
+            ("12px", f64::NAN),
+        ];
+
+        for (source, expected) in cases {
+            let actual = string_to_number(source);
+            assert!(
+                same_number(actual, expected),
+                "StringToNumber({:?}) is {}, not {}",
+                source,
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn number_to_string_matches_javascript() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (f64::NAN, "NaN"),
+            (f64::INFINITY, "Infinity"),
+            (f64::NEG_INFINITY, "-Infinity"),
+            (42.0, "42"),
+            (-1.5, "-1.5"),
+            (0.1 + 0.2, "0.30000000000000004"),
+            (123456789012345680000.0, "123456789012345680000"),
+            (1e21, "1e+21"),
+            (1.5e300, "1.5e+300"),
+            (0.000001, "0.000001"),
+            (1e-7, "1e-7"),
+            (-2.5e-10, "-2.5e-10"),
+        ];
+
+        for (n, expected) in cases {
+            assert_eq!(number_to_string(n), expected, "Number::toString({})", n);
+        }
+    }
+
+    #[test]
+    fn to_number_and_to_string_of_every_type() {
+        let mut interpreter = Interpreter::new();
+        let string = |s: &str| Value::String(s.to_string());
+
+        let cases = [
+            (Value::Undefined, f64::NAN, "undefined"),
+            (Value::Null, 0.0, "null"),
+            (Value::Boolean(true), 1.0, "true"),
+            (Value::Boolean(false), 0.0, "false"),
+            (Value::Number(-0.0), -0.0, "0"),
+            (string(""), 0.0, ""),
+            (string(" 12 "), 12.0, " 12 "),
+            (string("1px"), f64::NAN, "1px"),
+            (evaluate("[]"), 0.0, ""),
+            (evaluate("[7]"), 7.0, "7"),
+            (evaluate("[1, [2, 3]]"), f64::NAN, "1,2,3"),
+            (evaluate("[null, undefined]"), f64::NAN, ","),
+            (evaluate("({})"), f64::NAN, "[object Object]"),
+            (evaluate("({ valueOf: () => 42 })"), 42.0, "[object Object]"),
+            (evaluate(r#"({ toString: () => "5" })"#), 5.0, "5"),
+        ];
+
+        for (value, number, expected) in cases {
+            let actual = to_number(&mut interpreter, &value).expect("ToNumber does not throw");
+            assert!(
+                same_number(actual, number),
+                "ToNumber({}) is {}, not {}",
+                value,
+                actual,
+                number
+            );
+            let actual = to_string(&mut interpreter, &value).expect("ToString does not throw");
+            assert_eq!(actual, expected, "ToString({})", value);
+        }
+    }
+
+    #[test]
+    fn loose_equality_converts_towards_numbers() {
+        let mut interpreter = Interpreter::new();
+        let number = Value::Number;
+        let string = |s: &str| Value::String(s.to_string());
+
+        let cases = [
+            (Value::Null, Value::Undefined, true),
+            (Value::Undefined, Value::Undefined, true),
+            (Value::Null, number(0.0), false),
+            (Value::Undefined, Value::Boolean(false), false),
+            (number(f64::NAN), number(f64::NAN), false),
+            (number(0.0), number(-0.0), true),
+            (number(1.0), string("1"), true),
+            (string(""), number(0.0), true),
+            (string(" \n"), number(0.0), true),
+            (string("1"), Value::Boolean(true), true),
+            (string("2"), Value::Boolean(true), false),
+            (Value::Boolean(false), number(0.0), true),
+            (string("a"), string("a"), true),
+            (evaluate("[1]"), number(1.0), true),
+            (evaluate("[1, 2]"), string("1,2"), true),
+            (evaluate("[]"), Value::Boolean(false), true),
+            (evaluate("({})"), string("[object Object]"), true),
+            (evaluate("({})"), evaluate("({})"), false),
+            (evaluate("[]"), Value::Null, false),
+        ];
+
+        for (x, y, expected) in cases {
+            let actual = is_loosely_equal(&mut interpreter, &x, &y).expect("== does not throw");
+            assert_eq!(actual, expected, "{} == {}", x, y);
+        }
+    }
+
+    #[test]
+    fn is_less_than_compares_numbers_or_code_units() {
+        let mut interpreter = Interpreter::new();
+        let number = Value::Number;
+        let string = |s: &str| Value::String(s.to_string());
+
+        let cases = [
+            (number(1.0), number(2.0), Some(true)),
+            (number(2.0), number(1.0), Some(false)),
+            (number(-0.0), number(0.0), Some(false)),
+            (number(f64::NAN), number(1.0), None),
+            (number(1.0), number(f64::NAN), None),
+            (Value::Undefined, number(1.0), None),
+            (Value::Null, number(1.0), Some(true)),
+            // Strings compare by code units, not numerically.
+            (string("10"), string("9"), Some(true)),
+            (string("Z"), string("a"), Some(true)),
+            (string("ab"), string("abc"), Some(true)),
+            (string("\u{1F600}"), string("\u{FF61}"), Some(true)),
+            (string("10"), number(9.0), Some(false)),
+            (string("a"), number(1.0), None),
+            (evaluate("[2]"), number(10.0), Some(true)),
+        ];
+
+        for (x, y, expected) in cases {
+            let actual = is_less_than(&mut interpreter, &x, &y, true).expect("< does not throw");
+            assert_eq!(actual, expected, "IsLessThan({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn to_int32_and_to_uint32_wrap_around() {
+        let two_32 = 4294967296.0;
+        let cases = [
+            (0.0, 0, 0),
+            (-0.0, 0, 0),
+            (f64::NAN, 0, 0),
+            (f64::INFINITY, 0, 0),
+            (f64::NEG_INFINITY, 0, 0),
+            (3.7, 3, 3),
+            (-3.7, -3, 4294967293),
+            (-1.0, -1, 4294967295),
+            (2147483647.0, 2147483647, 2147483647),
+            (2147483648.0, -2147483648, 2147483648),
+            (two_32, 0, 0),
+            (two_32 + 5.0, 5, 5),
+            (-two_32 - 1.0, -1, 4294967295),
+            (1e21, -559939584, 3735027712),
+        ];
+
+        for (n, int32, uint32) in cases {
+            assert_eq!(to_int32(n), int32, "ToInt32({})", n);
+            assert_eq!(to_uint32(n), uint32, "ToUint32({})", n);
+        }
+    }
+
+    #[test]
+    fn to_boolean_conformance() {
+        let cases = [
+            (Value::Number(0.0), false),
+            (Value::Number(-0.0), false),
+            (Value::Number(f64::NAN), false),
+            (Value::Number(-1.0), true),
+            (Value::Number(0.5), true),
+            (Value::Number(f64::INFINITY), true),
+            (Value::Number(f64::NEG_INFINITY), true),
+            (Value::String(String::new()), false),
+            (Value::String("0".to_string()), true),
+            (Value::String("false".to_string()), true),
+            (Value::Boolean(false), false),
+            (Value::Boolean(true), true),
+            (Value::Null, false),
+            (Value::Undefined, false),
+            (evaluate("({})"), true),
+            (evaluate("({ valueOf: () => 0 })"), true),
+            (evaluate("[]"), true),
+            (evaluate("[0]"), true),
+            (evaluate("(function () {})"), true),
+            (evaluate("() => 0"), true),
+            (evaluate("(class {})"), true),
+            (evaluate("Object.create"), true),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(to_boolean(&value), expected, "ToBoolean({})", value);
+        }
+    }
+}
@@ -1,17 +1,72 @@
+use std::collections::HashMap;
+
 use crate::ast::*;
+use crate::error::{JustError, Span};
 use crate::lexer::{Token, TokenType};
+use crate::operations::number_to_string;
+use crate::stack::{StackGuard, DEFAULT_STACK_SIZE};
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    errors: Vec<JustError>,
+    /// The `=` of a shorthand `{ a = 1 }` that is only valid if the object
+    /// literal around it turns out to be a destructuring assignment target.
+    cover_initializer: Option<Token>,
+    /// The names declared in each enclosing scope, innermost last, mapped
+    /// to whether they were declared with `let`, `const` or `class`.
+    scopes: Vec<HashMap<String, bool>>,
+    /// Stops deeply nested source, like thousands of `(`, with a RangeError
+    /// before it overflows the native stack.
+    stack: StackGuard,
+    /// Where `break`, `continue` and `return` may jump to from the statement
+    /// being parsed.
+    jumps: JumpTargets,
+}
+
+/// The statements around the current one that a jump can target. Function
+/// bodies start afresh, since no jump crosses a function boundary.
+#[derive(Debug, Default)]
+struct JumpTargets {
+    /// The enclosing labels, innermost last, with whether each labels a
+    /// loop that `continue` can target.
+    labels: Vec<(String, bool)>,
+    /// How many loops enclose the statement.
+    loops: usize,
+    /// Whether the statement is in a function body, where `return` is
+    /// allowed.
+    in_function: bool,
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+    pub fn new(mut tokens: Vec<Token>) -> Self {
+        tokens.retain(|token| !matches!(token.ttype, TokenType::LineComment(_)));
+
+        Self {
+            tokens,
+            current: 0,
+            errors: Vec::new(),
+            cover_initializer: None,
+            // `undefined` is a global of its own that a script cannot
+            // shadow with a `let`, `const` or `class`.
+            scopes: vec![HashMap::from([("undefined".to_string(), false)])],
+            stack: StackGuard::new(DEFAULT_STACK_SIZE),
+            jumps: JumpTargets::default(),
+        }
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Statement>, String> {
+    /// Sets how much stack the thread running the parser has, as
+    /// `Interpreter::with_stack_size` does for the interpreter.
+    pub fn with_stack_size(mut self, size: usize) -> Self {
+        self.stack = StackGuard::new(size);
+        self
+    }
+
+    /// Parses the whole program. After a syntax error the parser skips to
+    /// the next statement boundary and carries on, so it returns every
+    /// statement it could parse together with every syntax error it found.
+    pub fn parse(&mut self) -> (Vec<Statement>, Vec<JustError>) {
+        self.stack.reset();
         let mut statements = Vec::new();
 
         while !self.is_at_end() {
@@ -20,20 +75,88 @@ impl Parser {
                 continue;
             }
 
-            statements.push(self.statement()?);
+            let start = self.current;
+            match self.statement() {
+                Ok(statement) => statements.push(statement),
+                Err(err) => {
+                    self.errors.push(err);
+                    self.synchronize(start, false);
+                }
+            }
             self.advance();
         }
 
-        Ok(statements)
+        (statements, std::mem::take(&mut self.errors))
+    }
+
+    /// Skips the rest of a statement starting at token `start` that failed
+    /// to parse, leaving the parser on its last token so the caller's
+    /// `advance` lands on the start of the next statement. Braced blocks are
+    /// skipped as a whole, and the `}` closing an enclosing block is left for
+    /// that block to consume.
+    fn synchronize(&mut self, start: usize, in_block: bool) {
+        // The statement ran into the start of the next one, e.g. `let a = 1 +`
+        // followed by `const b = 2`.
+        if self.current > start && starts_statement(&self.peek().ttype) {
+            self.current -= 1;
+            return;
+        }
+
+        // A class body is made of members rather than statements, so the
+        // whole class is skipped.
+        if self.tokens[start].ttype == TokenType::Class {
+            self.current = start;
+        }
+
+        let mut depth = 0;
+
+        while !self.is_at_end() {
+            match self.peek().ttype {
+                TokenType::LBrace => depth += 1,
+                TokenType::RBrace if depth > 1 => depth -= 1,
+                TokenType::RBrace if depth == 1 => return,
+                TokenType::RBrace => {
+                    if in_block {
+                        self.current -= 1;
+                    }
+                    return;
+                }
+                TokenType::SemiColon if depth == 0 => return,
+                _ => {}
+            }
+
+            if depth == 0 && starts_statement(&self.peek_next().ttype) {
+                return;
+            }
+
+            self.advance();
+        }
     }
 
-    fn statement(&mut self) -> Result<Statement, String> {
+    fn statement(&mut self) -> Result<Statement, JustError> {
+        self.check_stack()?;
+
         match self.peek().ttype {
-            TokenType::Let | TokenType::Var => self.variable_declaration(true),
-            TokenType::Const => self.variable_declaration(false),
+            TokenType::Var => self.variable_declaration(DeclarationKind::Var),
+            TokenType::Let => self.variable_declaration(DeclarationKind::Let),
+            TokenType::Const => self.variable_declaration(DeclarationKind::Const),
             TokenType::LBrace => self.block(),
             TokenType::Function => self.function_declaration(),
+            TokenType::Class => Ok(Statement::Class(self.class(true)?)),
+            TokenType::If => self.if_statement(),
+            TokenType::While => self.while_statement(),
+            TokenType::For => self.for_statement(),
+            TokenType::Break => Ok(Statement::Break(self.jump_label()?)),
+            TokenType::Continue => Ok(Statement::Continue(self.jump_label()?)),
+            TokenType::Identifier(_) if self.peek_next().ttype == TokenType::Colon => {
+                self.labeled_statement()
+            }
+            // An empty statement, e.g. the body of `while (step());`
+            TokenType::SemiColon => Ok(Statement::Block(Vec::new())),
             TokenType::Return => {
+                if !self.jumps.in_function {
+                    return Err(JustError::syntax("Illegal return statement", self.peek()));
+                }
                 self.advance();
                 match self.peek().ttype {
                     TokenType::SemiColon => Ok(Statement::Return(None)),
@@ -50,34 +173,339 @@ impl Parser {
         }
     }
 
-    fn variable_declaration(&mut self, can_reassign: bool) -> Result<Statement, String> {
+    fn variable_declaration(&mut self, kind: DeclarationKind) -> Result<Statement, JustError> {
+        let mut declarators = vec![self.declarator(kind)?];
+        while self.peek_next().ttype == TokenType::Comma {
+            self.advance();
+            declarators.push(self.declarator(kind)?);
+        }
+
+        Ok(Statement::VariableDeclaration { kind, declarators })
+    }
+
+    /// Parses one `target = initializer` of a declaration, starting on the
+    /// token before it.
+    fn declarator(&mut self, kind: DeclarationKind) -> Result<Declarator, JustError> {
         self.advance();
+        let start = self.current;
         let token = self.peek();
-        let name = match &token.ttype {
-            TokenType::Identifier(v) => v.to_string(),
-            _ => {
-                let err_message = format!("Invalid variable name at {}:{}", token.line, token.col);
-                return Err(err_message);
-            }
+        if !matches!(
+            token.ttype,
+            TokenType::Identifier(_) | TokenType::LBracket | TokenType::LBrace
+        ) {
+            return Err(JustError::syntax("Invalid variable name", token));
+        }
+        let pattern = self.pattern()?;
+        self.declare(pattern.bound_names(), kind.is_lexical(), start)?;
+
+        let initializer = if self.peek_next().ttype == TokenType::Equal {
+            self.advance();
+            self.advance();
+            Some(self.assignment_expression()?)
+        } else if !matches!(pattern, Pattern::Identifier(_)) {
+            return Err(JustError::syntax(
+                "Missing initializer in destructuring declaration",
+                self.peek(),
+            ));
+        } else if kind != DeclarationKind::Const {
+            None
+        } else {
+            return Err(JustError::syntax(
+                "Missing initializer in const declaration",
+                self.peek(),
+            ));
         };
 
+        Ok(Declarator {
+            pattern,
+            initializer,
+        })
+    }
+
+    fn if_statement(&mut self) -> Result<Statement, JustError> {
+        self.advance();
+        let condition = self.parenthesized_condition()?;
         self.advance();
+        let then_branch = Box::new(self.statement()?);
 
-        let initializer = if self.peek().ttype == TokenType::Equal {
+        // A brace-less branch may be terminated by `;` before the `else`,
+        // e.g. `if (a) b = 1; else b = 2;`
+        if self.peek_next().ttype == TokenType::SemiColon
+            && self.peek_at(2).ttype == TokenType::Else
+        {
+            self.advance();
+        }
+
+        let else_branch = if self.peek_next().ttype == TokenType::Else {
             self.advance();
-            Some(self.expression()?)
+            self.advance();
+            Some(Box::new(self.statement()?))
         } else {
             None
         };
 
-        Ok(Statement::VariableDeclaration {
-            name,
+        Ok(Statement::If {
+            condition,
+            then_branch,
+            else_branch,
+        })
+    }
+
+    fn while_statement(&mut self) -> Result<Statement, JustError> {
+        self.advance();
+        let condition = self.parenthesized_condition()?;
+        self.advance();
+        let body = Box::new(self.loop_body()?);
+
+        Ok(Statement::While { condition, body })
+    }
+
+    /// Parses any `for` loop. Declarations in its head get a scope of their
+    /// own, around the body's.
+    fn for_statement(&mut self) -> Result<Statement, JustError> {
+        self.with_scope(HashMap::new(), Self::for_loop)
+    }
+
+    fn for_loop(&mut self) -> Result<Statement, JustError> {
+        self.advance();
+        self.check(TokenType::LParen, "(")?;
+        self.advance();
+
+        if let Some(target) = self.for_target()? {
+            let is_of = self.peek().ttype != TokenType::In;
+            self.advance();
+            // `for (x of a, b)` is not a sequence, unlike `for (x in a, b)`.
+            let subject = if is_of {
+                self.assignment_expression()?
+            } else {
+                self.expression()?
+            };
+            self.advance();
+            self.check(TokenType::RParen, ")")?;
+            self.advance();
+            let body = Box::new(self.loop_body()?);
+
+            return Ok(if is_of {
+                Statement::ForOf {
+                    target,
+                    iterable: subject,
+                    body,
+                }
+            } else {
+                Statement::ForIn {
+                    target,
+                    object: subject,
+                    body,
+                }
+            });
+        }
+
+        let initializer = match self.peek().ttype {
+            TokenType::SemiColon => None,
+            TokenType::Var | TokenType::Let | TokenType::Const => {
+                let kind = match self.peek().ttype {
+                    TokenType::Var => DeclarationKind::Var,
+                    TokenType::Let => DeclarationKind::Let,
+                    _ => DeclarationKind::Const,
+                };
+                let declaration = self.variable_declaration(kind)?;
+                self.advance();
+                Some(Box::new(declaration))
+            }
+            _ => {
+                let expression = self.expression()?;
+                self.advance();
+                Some(Box::new(Statement::Expression(expression)))
+            }
+        };
+        self.check(TokenType::SemiColon, ";")?;
+        self.advance();
+
+        let condition = if self.peek().ttype == TokenType::SemiColon {
+            None
+        } else {
+            let condition = self.expression()?;
+            self.advance();
+            Some(condition)
+        };
+        self.check(TokenType::SemiColon, ";")?;
+        self.advance();
+
+        let update = if self.peek().ttype == TokenType::RParen {
+            None
+        } else {
+            let update = self.expression()?;
+            self.advance();
+            Some(update)
+        };
+        self.check(TokenType::RParen, ")")?;
+        self.advance();
+
+        let body = Box::new(self.loop_body()?);
+
+        Ok(Statement::For {
             initializer,
-            can_reassign,
+            condition,
+            update,
+            body,
+        })
+    }
+
+    /// Parses the binding of a `for...in` / `for...of` head if there is one,
+    /// leaving the parser on the `in` or `of` keyword. Returns `None` without
+    /// consuming anything for a classic `for (init; cond; update)` head.
+    fn for_target(&mut self) -> Result<Option<ForTarget>, JustError> {
+        let (kind, offset) = match self.peek().ttype {
+            TokenType::Var => (Some(DeclarationKind::Var), 1),
+            TokenType::Let => (Some(DeclarationKind::Let), 1),
+            TokenType::Const => (Some(DeclarationKind::Const), 1),
+            _ => (None, 0),
+        };
+
+        let end = match &self.peek_at(offset).ttype {
+            TokenType::Identifier(_) => offset,
+            // Only declarations destructure: `for (const [k, v] of entries)`
+            TokenType::LBracket | TokenType::LBrace if offset == 1 => {
+                match self.closing_token(offset) {
+                    Some(end) => end,
+                    None => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        match &self.peek_at(end + 1).ttype {
+            TokenType::In => {}
+            TokenType::Identifier(v) if v == "of" => {}
+            _ => return Ok(None),
+        }
+
+        for _ in 0..offset {
+            self.advance();
+        }
+
+        let start = self.current;
+        let target = match (kind, &self.peek().ttype) {
+            (Some(kind), _) => {
+                let pattern = self.pattern()?;
+                self.declare(pattern.bound_names(), kind.is_lexical(), start)?;
+                ForTarget::Declaration(kind, pattern)
+            }
+            (None, TokenType::Identifier(name)) => ForTarget::Variable(name.to_string()),
+            (None, _) => unreachable!("checked above"),
+        };
+        self.advance();
+
+        Ok(Some(target))
+    }
+
+    /// Parses the optional label after `break` or `continue`. A label has to
+    /// start on the same line as the keyword, and the jump needs a statement
+    /// around it to go to.
+    fn jump_label(&mut self) -> Result<Option<String>, JustError> {
+        let is_continue = self.peek().ttype == TokenType::Continue;
+        let keyword_line = self.peek().line;
+        let next = self.peek_next();
+
+        let label = match &next.ttype {
+            TokenType::Identifier(label) if next.line == keyword_line => {
+                let label = label.to_string();
+                self.advance();
+                label
+            }
+            _ if is_continue && self.jumps.loops == 0 => {
+                return Err(JustError::syntax(
+                    "Illegal continue statement: no surrounding iteration statement",
+                    self.peek(),
+                ));
+            }
+            _ if self.jumps.loops == 0 => {
+                return Err(JustError::syntax("Illegal break statement", self.peek()));
+            }
+            _ => return Ok(None),
+        };
+
+        match self
+            .jumps
+            .labels
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == label)
+        {
+            None => Err(JustError::syntax(
+                format!("Undefined label '{}'", label),
+                self.peek(),
+            )),
+            Some((_, false)) if is_continue => Err(JustError::syntax(
+                format!(
+                    "Illegal continue statement: '{}' does not denote an iteration statement",
+                    label
+                ),
+                self.peek(),
+            )),
+            Some(_) => Ok(Some(label)),
+        }
+    }
+
+    /// Parses `label: statement`, where the statement can `break` to the
+    /// label, or `continue` to it if it is a loop.
+    fn labeled_statement(&mut self) -> Result<Statement, JustError> {
+        let label = self.peek().lexeme.to_string();
+        if self.jumps.labels.iter().any(|(name, _)| *name == label) {
+            return Err(JustError::syntax(
+                format!("Label '{}' has already been declared", label),
+                self.peek(),
+            ));
+        }
+        self.advance();
+        self.advance();
+
+        // A label on a label on a loop labels that loop too.
+        let mut ahead = self.current;
+        while matches!(self.tokens[ahead].ttype, TokenType::Identifier(_))
+            && self.tokens[ahead + 1].ttype == TokenType::Colon
+        {
+            ahead += 2;
+        }
+        let is_loop = matches!(self.tokens[ahead].ttype, TokenType::While | TokenType::For);
+
+        self.jumps.labels.push((label.clone(), is_loop));
+        let body = self.statement();
+        self.jumps.labels.pop();
+
+        Ok(Statement::Labeled {
+            label,
+            body: Box::new(body?),
         })
     }
 
-    fn block(&mut self) -> Result<Statement, String> {
+    /// Parses the body of a loop, which `break` and `continue` can leave.
+    fn loop_body(&mut self) -> Result<Statement, JustError> {
+        self.jumps.loops += 1;
+        let body = self.statement();
+        self.jumps.loops -= 1;
+
+        body
+    }
+
+    /// Parses the `(condition)` of an `if` or `while`, leaving the parser on
+    /// the closing `)`.
+    fn parenthesized_condition(&mut self) -> Result<Expression, JustError> {
+        self.check(TokenType::LParen, "(")?;
+        self.advance();
+        let condition = self.expression()?;
+        self.advance();
+        self.check(TokenType::RParen, ")")?;
+
+        Ok(condition)
+    }
+
+    fn block(&mut self) -> Result<Statement, JustError> {
+        self.with_scope(HashMap::new(), Self::block_contents)
+    }
+
+    /// Parses a braced block in the scope the caller entered.
+    fn block_contents(&mut self) -> Result<Statement, JustError> {
         self.check(TokenType::LBrace, "{")?;
         self.advance();
         let mut statements = Vec::new();
@@ -85,11 +513,10 @@ impl Parser {
         loop {
             let current_token = self.peek();
             if self.is_at_end() {
-                let err = format!(
-                    "You must close function block with '}}' at {}:{}",
-                    current_token.line, current_token.col
-                );
-                return Err(err);
+                return Err(JustError::syntax(
+                    "You must close block with '}'",
+                    current_token,
+                ));
             }
 
             if self.peek().ttype == TokenType::SemiColon {
@@ -101,197 +528,1128 @@ impl Parser {
                 break;
             }
 
-            statements.push(self.statement()?);
+            let start = self.current;
+            match self.statement() {
+                Ok(statement) => statements.push(statement),
+                Err(err) => {
+                    self.errors.push(err);
+                    self.synchronize(start, true);
+                }
+            }
             self.advance();
         }
 
         Ok(Statement::Block(statements))
     }
 
-    fn function_declaration(&mut self) -> Result<Statement, String> {
+    fn function_declaration(&mut self) -> Result<Statement, JustError> {
         self.advance();
 
         let token = self.peek();
         let name = match &token.ttype {
             TokenType::Identifier(v) => v.to_string(),
-            _ => {
-                let err_message = format!("Invalid variable name at {}:{}", token.line, token.col);
-                return Err(err_message);
-            }
+            _ => return Err(JustError::syntax("Invalid function name", token)),
         };
+        self.declare(vec![name.clone()], false, self.current)?;
 
-        let mut params = Vec::new();
+        self.advance();
+        let (parameters, body) = self.function_rest()?;
+
+        Ok(Statement::FunctionDeclaration {
+            name,
+            parameters,
+            body,
+        })
+    }
 
+    /// Parses a function's parameter list and body, starting on the `(` and
+    /// leaving the parser on the closing `}`.
+    fn function_rest(&mut self) -> Result<(Vec<Parameter>, Vec<Statement>), JustError> {
+        let parameters = self.parameters()?;
         self.advance();
+
+        let body = self.function_body(&parameters)?;
+
+        Ok((parameters, body))
+    }
+
+    /// Parses a parameter list, starting on the `(` and leaving the parser
+    /// on the closing `)`.
+    fn parameters(&mut self) -> Result<Vec<Parameter>, JustError> {
+        let mut params = Vec::new();
+
         self.check(TokenType::LParen, "(")?;
-        self.advance();
 
         loop {
-            match &self.peek().ttype {
-                TokenType::Identifier(v) => params.push(v.to_string()),
-                TokenType::RParen => {
-                    self.advance();
-                    break;
-                }
-                TokenType::Comma => {}
-                _ => {
-                    let token = self.peek();
-                    let err_message = format!(
-                        "Syntax Error expected ), but get {} at {}:{}",
-                        token.lexeme, token.line, token.col
-                    );
-                    return Err(err_message);
-                }
+            self.advance();
+            if self.peek().ttype == TokenType::RParen {
+                break;
             }
 
-            self.advance();
-        }
+            let rest = self.peek().ttype == TokenType::Ellipsis;
+            if rest {
+                self.advance();
+            }
 
-        self.check(TokenType::LBrace, "{")?;
+            let token = self.peek();
+            if !matches!(
+                token.ttype,
+                TokenType::Identifier(_) | TokenType::LBracket | TokenType::LBrace
+            ) {
+                let message = format!("Expected ')', but got {}", token.lexeme);
+                return Err(JustError::syntax(message, token));
+            }
+            let pattern = self.pattern()?;
 
-        match self.block() {
-            Ok(v) => {
-                if let Statement::Block(body) = v {
-                    Ok(Statement::FunctionDeclaration {
-                        name,
-                        parameters: params,
-                        body,
-                    })
-                } else {
-                    Err("Block is not Vec<Statement>".to_string())
+            let default = if self.peek_next().ttype == TokenType::Equal {
+                self.advance();
+                if rest {
+                    return Err(JustError::syntax(
+                        "Rest parameter may not have a default initializer",
+                        self.peek(),
+                    ));
+                }
+                self.advance();
+                Some(self.assignment_expression()?)
+            } else {
+                None
+            };
+
+            params.push(Parameter {
+                pattern,
+                default,
+                rest,
+            });
+
+            self.advance();
+            match self.peek().ttype {
+                TokenType::Comma if rest => {
+                    return Err(JustError::syntax(
+                        "Rest parameter must be last formal parameter",
+                        self.peek(),
+                    ))
                 }
+                TokenType::Comma => {}
+                TokenType::RParen => break,
+                _ => self.check(TokenType::RParen, ")")?,
             }
-            Err(e) => Err(e),
         }
-    }
 
-    fn expression(&mut self) -> Result<Expression, String> {
-        let expression = self.equality()?;
-
-        Ok(expression)
+        Ok(params)
     }
 
-    fn equality(&mut self) -> Result<Expression, String> {
-        let mut expression = self.comparison()?;
-        let mut next_ttype = &self.peek_next().ttype;
-
-        while *next_ttype == TokenType::EqualEqual || *next_ttype == TokenType::BangEqual {
-            self.advance();
-            let operator = self.peek().ttype.clone();
-            self.advance();
-            let right = self.expression()?;
+    /// Parses a binding pattern: a name, `[a, , b = 1, ...rest]` or
+    /// `{ a, b: c = 1, [key]: d, ...rest }`. Starts on the first token and
+    /// leaves the parser on the last.
+    fn pattern(&mut self) -> Result<Pattern, JustError> {
+        self.check_stack()?;
+        let token = self.peek();
 
-            expression = Expression::Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            };
+        match &token.ttype {
+            TokenType::Identifier(name) => Ok(Pattern::Identifier(name.to_string())),
+            TokenType::LBracket => self.array_pattern(),
+            TokenType::LBrace => self.object_pattern(),
+            _ => Err(JustError::syntax(
+                "Invalid destructuring assignment target",
+                token,
+            )),
+        }
+    }
 
-            next_ttype = &self.peek().ttype;
+    /// Parses the `= default` after a pattern element if there is one,
+    /// leaving the parser on the last token of the element.
+    fn pattern_default(&mut self) -> Result<Option<Expression>, JustError> {
+        if self.peek_next().ttype != TokenType::Equal {
+            return Ok(None);
         }
 
-        Ok(expression)
+        self.advance();
+        self.advance();
+        Ok(Some(self.assignment_expression()?))
     }
 
-    fn comparison(&mut self) -> Result<Expression, String> {
-        let mut expression = self.term()?;
-        let mut next_ttype = &self.peek_next().ttype;
+    /// Parses `[a, , b = 1, ...rest]`, starting on the `[` and leaving the
+    /// parser on the closing `]`.
+    fn array_pattern(&mut self) -> Result<Pattern, JustError> {
+        let start = Span::from(self.peek());
+        let mut elements = Vec::new();
+        let mut rest = None;
 
-        while *next_ttype == TokenType::Greater
-            || *next_ttype == TokenType::GreaterEqual
-            || *next_ttype == TokenType::Lesser
-            || *next_ttype == TokenType::LesserEqual
-        {
-            self.advance();
-            let operator = self.peek().ttype.clone();
+        loop {
             self.advance();
-            let right = self.expression()?;
+            match self.peek().ttype {
+                TokenType::RBracket => break,
+                TokenType::Comma => {
+                    elements.push(None);
+                    continue;
+                }
+                TokenType::Ellipsis => {
+                    self.advance();
+                    rest = Some(Box::new(self.pattern()?));
+                    self.advance();
+                    self.rest_element_end(TokenType::RBracket)?;
+                    break;
+                }
+                _ => {}
+            }
 
-            expression = Expression::Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            };
+            let target = self.pattern()?;
+            let default = self.pattern_default()?;
+            elements.push(Some(PatternElement { target, default }));
 
-            next_ttype = &self.peek().ttype;
+            self.advance();
+            match self.peek().ttype {
+                TokenType::Comma => {}
+                TokenType::RBracket => break,
+                _ => self.check(TokenType::RBracket, "]")?,
+            }
         }
 
-        Ok(expression)
+        Ok(Pattern::Array {
+            elements,
+            rest,
+            span: start.to(self.peek().into()),
+        })
     }
 
-    fn term(&mut self) -> Result<Expression, String> {
-        let mut expression = self.factor()?;
-        let mut next_ttype = &self.peek_next().ttype;
+    /// Parses `{ a, b: c = 1, [key]: d, ...rest }`, starting on the `{` and
+    /// leaving the parser on the closing `}`.
+    fn object_pattern(&mut self) -> Result<Pattern, JustError> {
+        let start = Span::from(self.peek());
+        let mut properties = Vec::new();
+        let mut rest = None;
 
-        while *next_ttype == TokenType::Plus || *next_ttype == TokenType::Minus {
-            self.advance();
-            let operator = self.peek().ttype.clone();
+        loop {
             self.advance();
-            let right = self.expression()?;
+            let token = self.peek().clone();
+            let (key, computed) =
+                match &token.ttype {
+                    TokenType::RBrace => break,
+                    TokenType::Ellipsis => {
+                        self.advance();
+                        let target = self.peek();
+                        match &target.ttype {
+                            TokenType::Identifier(name) => {
+                                rest = Some(Box::new(Pattern::Identifier(name.to_string())))
+                            }
+                            _ => return Err(JustError::syntax(
+                                "`...` must be followed by an identifier in declaration contexts",
+                                target,
+                            )),
+                        }
+                        self.advance();
+                        self.rest_element_end(TokenType::RBrace)?;
+                        break;
+                    }
+                    TokenType::LBracket => {
+                        self.advance();
+                        let key = self.assignment_expression()?;
+                        self.advance();
+                        self.check(TokenType::RBracket, "]")?;
+                        (key, true)
+                    }
+                    TokenType::String(key) => {
+                        (Expression::Literal(Literal::String(key.to_string())), false)
+                    }
+                    TokenType::Number(n) => (
+                        Expression::Literal(Literal::String(number_to_string(*n))),
+                        false,
+                    ),
+                    _ => match property_name(&token) {
+                        Some(key) => (Expression::Literal(Literal::String(key)), false),
+                        None => {
+                            let message = format!("Unexpected token {:?}", token.lexeme);
+                            return Err(JustError::syntax(message, &token));
+                        }
+                    },
+                };
 
-            expression = Expression::Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
+            let target = match (&token.ttype, &self.peek_next().ttype) {
+                (_, TokenType::Colon) => {
+                    self.advance();
+                    self.advance();
+                    self.pattern()?
+                }
+                // Shorthand `{ a }` for `{ a: a }`
+                (TokenType::Identifier(name), _) => Pattern::Identifier(name.to_string()),
+                _ => {
+                    let token = self.peek_next();
+                    let message = format!("Unexpected token, expected :, but got {}", token.lexeme);
+                    return Err(JustError::syntax(message, token));
+                }
             };
+            let default = self.pattern_default()?;
+            properties.push(PatternProperty {
+                key,
+                computed,
+                value: PatternElement { target, default },
+            });
 
-            next_ttype = &self.peek().ttype;
+            self.advance();
+            match self.peek().ttype {
+                TokenType::Comma => {}
+                TokenType::RBrace => break,
+                _ => self.check(TokenType::RBrace, "}")?,
+            }
         }
 
-        Ok(expression)
+        Ok(Pattern::Object {
+            properties,
+            rest,
+            span: start.to(self.peek().into()),
+        })
     }
 
-    fn factor(&mut self) -> Result<Expression, String> {
-        let mut expression = self.unary()?;
-        let mut next_ttype = &self.peek_next().ttype;
+    /// Checks that a rest element is followed by the `close` of its
+    /// pattern, where the parser is expected to be.
+    fn rest_element_end(&self, close: TokenType) -> Result<(), JustError> {
+        if self.peek().ttype == close {
+            return Ok(());
+        }
 
-        while *next_ttype == TokenType::Slash
-            || *next_ttype == TokenType::Star
-            || *next_ttype == TokenType::Power
-            || *next_ttype == TokenType::Percent
-        {
-            self.advance();
-            let operator = self.peek().ttype.clone();
-            self.advance();
-            let right = self.expression()?;
+        Err(JustError::syntax(
+            "Rest element must be last element",
+            self.peek(),
+        ))
+    }
 
-            expression = Expression::Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            };
+    fn function_body(&mut self, parameters: &[Parameter]) -> Result<Vec<Statement>, JustError> {
+        self.check(TokenType::LBrace, "{")?;
 
-            next_ttype = &self.peek().ttype;
-        }
+        // The parameters share a scope with the body's own declarations.
+        let scope = parameters
+            .iter()
+            .flat_map(|parameter| parameter.pattern.bound_names())
+            .map(|name| (name, false))
+            .collect();
 
-        Ok(expression)
+        let jumps = JumpTargets {
+            in_function: true,
+            ..JumpTargets::default()
+        };
+        let body = self.with_jumps(jumps, |parser| {
+            parser.with_scope(scope, Self::block_contents)
+        })?;
+
+        match body {
+            Statement::Block(body) => Ok(body),
+            _ => unreachable!("block() always returns Statement::Block"),
+        }
     }
 
-    fn unary(&mut self) -> Result<Expression, String> {
-        let token = self.peek();
+    /// Parses `function name(parameters) { ... }` in expression position,
+    /// where the name is optional.
+    fn function_expression(&mut self) -> Result<Expression, JustError> {
+        self.advance();
 
-        let result = match &token.ttype {
-            TokenType::Minus => {
+        let name = match &self.peek().ttype {
+            TokenType::Identifier(name) => {
+                let name = name.to_string();
+                self.advance();
+                Some(name)
+            }
+            _ => None,
+        };
+        let (parameters, body) = self.function_rest()?;
+
+        Ok(Expression::Function {
+            name,
+            parameters,
+            body,
+        })
+    }
+
+    /// Whether the `(` the parser is on opens the parameters of an arrow
+    /// function, i.e. its matching `)` is followed by `=>`.
+    fn at_arrow_parameters(&self) -> bool {
+        self.closing_token(0)
+            .is_some_and(|end| self.peek_at(end + 1).ttype == TokenType::Arrow)
+    }
+
+    /// The offset of the bracket that closes the one `offset` tokens ahead.
+    fn closing_token(&self, offset: usize) -> Option<usize> {
+        let mut depth = 0;
+
+        for (index, token) in self.tokens[self.current + offset..].iter().enumerate() {
+            match token.ttype {
+                TokenType::LParen | TokenType::LBracket | TokenType::LBrace => depth += 1,
+                TokenType::RParen | TokenType::RBracket | TokenType::RBrace => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(offset + index);
+                    }
+                }
+                TokenType::Eof => return None,
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    /// Parses the body of an arrow function, starting on the `=>`. A
+    /// concise body is an expression whose value is returned.
+    fn arrow_function(&mut self, parameters: Vec<Parameter>) -> Result<Expression, JustError> {
+        self.advance();
+
+        let body = if self.peek().ttype == TokenType::LBrace {
+            self.function_body(&parameters)?
+        } else {
+            vec![Statement::Return(Some(self.assignment_expression()?))]
+        };
+
+        Ok(Expression::Arrow { parameters, body })
+    }
+
+    /// Parses a class declaration or expression, starting on `class` and
+    /// leaving the parser on the closing `}`. Only declarations require a
+    /// name.
+    fn class(&mut self, declaration: bool) -> Result<Class, JustError> {
+        self.advance();
+
+        let name = match &self.peek().ttype {
+            TokenType::Identifier(name) => {
+                let name = name.to_string();
+                if declaration {
+                    self.declare(vec![name.clone()], true, self.current)?;
+                }
+                self.advance();
+                Some(name)
+            }
+            _ if declaration => {
+                return Err(JustError::syntax("Invalid class name", self.peek()));
+            }
+            _ => None,
+        };
+
+        let superclass = if self.peek().ttype == TokenType::Extends {
+            self.advance();
+            let superclass = self.member(true)?;
+            self.advance();
+            Some(superclass)
+        } else {
+            None
+        };
+
+        self.check(TokenType::LBrace, "{")?;
+
+        let mut class = Class {
+            name,
+            superclass,
+            constructor: None,
+            members: Vec::new(),
+        };
+
+        loop {
+            self.advance();
+            match self.peek().ttype {
+                TokenType::RBrace => break,
+                TokenType::SemiColon => continue,
+                TokenType::Eof => {
+                    return Err(JustError::syntax(
+                        "You must close class body with '}'",
+                        self.peek(),
+                    ))
+                }
+                _ => {}
+            }
+
+            let start = self.peek().clone();
+            match self.class_member()? {
+                ClassMember::Method {
+                    key: ClassKey::Named(name),
+                    kind,
+                    is_static: false,
+                    parameters,
+                    body,
+                } if name == "constructor" => {
+                    if kind != MethodKind::Method {
+                        return Err(JustError::syntax(
+                            "Class constructor may not be an accessor",
+                            &start,
+                        ));
+                    }
+                    if class.constructor.is_some() {
+                        return Err(JustError::syntax(
+                            "A class may only have one constructor",
+                            &start,
+                        ));
+                    }
+
+                    class.constructor = Some((parameters, body));
+                }
+                member => class.members.push(member),
+            }
+        }
+
+        Ok(class)
+    }
+
+    /// Parses one method, field or static block of a class body, leaving the
+    /// parser on its last token.
+    fn class_member(&mut self) -> Result<ClassMember, JustError> {
+        // `static` and `get`/`set` are modifiers unless they are the name of
+        // the member itself, as in `static() {}` or `get = 1`.
+        let is_modifier = |parser: &Parser| {
+            !matches!(
+                parser.peek_next().ttype,
+                TokenType::LParen | TokenType::Equal | TokenType::SemiColon | TokenType::RBrace
+            )
+        };
+
+        let is_static = self.peek().ttype == TokenType::Static && is_modifier(self);
+        if is_static {
+            self.advance();
+
+            if self.peek().ttype == TokenType::LBrace {
+                return match self.with_jumps(JumpTargets::default(), Self::block)? {
+                    Statement::Block(body) => Ok(ClassMember::StaticBlock(body)),
+                    _ => unreachable!("block() always returns Statement::Block"),
+                };
+            }
+        }
+
+        let kind = match &self.peek().ttype {
+            TokenType::Identifier(name) if name == "get" && is_modifier(self) => MethodKind::Getter,
+            TokenType::Identifier(name) if name == "set" && is_modifier(self) => MethodKind::Setter,
+            _ => MethodKind::Method,
+        };
+        if kind != MethodKind::Method {
+            self.advance();
+        }
+
+        let start = self.peek().clone();
+        let key = self.class_key()?;
+
+        if self.peek_next().ttype == TokenType::LParen {
+            if let (ClassKey::Private(_), MethodKind::Getter | MethodKind::Setter) = (&key, kind) {
+                return Err(JustError::syntax(
+                    "Private accessors are not supported",
+                    &start,
+                ));
+            }
+
+            self.advance();
+            let (parameters, body) = self.function_rest()?;
+
+            return Ok(ClassMember::Method {
+                key,
+                kind,
+                is_static,
+                parameters,
+                body,
+            });
+        }
+
+        if kind != MethodKind::Method {
+            let token = self.peek_next();
+            let message = format!("Unexpected token, expected (, but got {}", token.lexeme);
+            return Err(JustError::syntax(message, token));
+        }
+        if let ClassKey::Named(name) = &key {
+            if name == "constructor" {
+                return Err(JustError::syntax(
+                    "Classes may not have a field named 'constructor'",
+                    &start,
+                ));
+            }
+        }
+
+        let initializer = if self.peek_next().ttype == TokenType::Equal {
+            self.advance();
+            self.advance();
+            Some(self.assignment_expression()?)
+        } else {
+            None
+        };
+
+        if self.peek_next().ttype == TokenType::SemiColon {
+            self.advance();
+        }
+
+        Ok(ClassMember::Field {
+            key,
+            initializer,
+            is_static,
+        })
+    }
+
+    /// Parses the name of a class member, leaving the parser on its last
+    /// token.
+    fn class_key(&mut self) -> Result<ClassKey, JustError> {
+        let token = self.peek().clone();
+
+        match &token.ttype {
+            TokenType::String(key) => Ok(ClassKey::Named(key.to_string())),
+            TokenType::Number(n) => Ok(ClassKey::Named(number_to_string(*n))),
+            TokenType::PrivateName(name) => Ok(ClassKey::Private(name.to_string())),
+            TokenType::LBracket => {
+                self.advance();
+                let key = self.assignment_expression()?;
+                self.advance();
+                self.check(TokenType::RBracket, "]")?;
+                Ok(ClassKey::Computed(key))
+            }
+            _ => match property_name(&token) {
+                Some(name) => Ok(ClassKey::Named(name)),
+                None => {
+                    let message = format!("Unexpected token {:?}", token.lexeme);
+                    Err(JustError::syntax(message, &token))
+                }
+            },
+        }
+    }
+
+    /// Parses a full expression, which may be a sequence: `a, b`.
+    fn expression(&mut self) -> Result<Expression, JustError> {
+        self.expression_at(Precedence::Sequence)
+    }
+
+    /// Parses an expression that stops at a comma, as in an initializer or
+    /// where commas separate elements.
+    fn assignment_expression(&mut self) -> Result<Expression, JustError> {
+        self.expression_at(Precedence::Assignment)
+    }
+
+    fn expression_at(&mut self, min: Precedence) -> Result<Expression, JustError> {
+        let outer = self.cover_initializer.take();
+        let expression = self.parse_precedence(min)?;
+
+        // Nothing outside the expression can turn it into a pattern anymore.
+        if let Some(token) = self.cover_initializer.take() {
+            return Err(JustError::syntax(
+                "Invalid shorthand property initializer",
+                &token,
+            ));
+        }
+        self.cover_initializer = outer;
+
+        Ok(expression)
+    }
+
+    /// Parses an expression whose operators all bind at least as tightly as
+    /// `min`, by precedence climbing over the table in `infix_precedence`.
+    fn parse_precedence(&mut self, min: Precedence) -> Result<Expression, JustError> {
+        self.check_stack()?;
+        let start = Span::from(self.peek());
+        let outer = self.cover_initializer.take();
+        let mut expression = self.unary()?;
+
+        // A line break before `++` or `--` ends the expression, so that
+        // `a\n++b` is `a; ++b`.
+        let next = self.peek_next();
+        if matches!(next.ttype, TokenType::PlusPlus | TokenType::MinusMinus)
+            && next.line == self.peek().line
+        {
+            self.advance();
+            let operator = self.peek().clone();
+            expression = update(expression, operator, false)?;
+        }
+
+        loop {
+            let operator = self.peek_next().clone();
+            let precedence = match infix_precedence(&operator.ttype) {
+                Some(precedence) if precedence >= min => precedence,
+                _ => break,
+            };
+
+            self.advance();
+            self.advance();
+
+            expression = match precedence {
+                Precedence::Sequence => {
+                    let mut expressions = match expression {
+                        Expression::Sequence(expressions) => expressions,
+                        expression => vec![expression],
+                    };
+                    expressions.push(self.parse_precedence(Precedence::Assignment)?);
+                    Expression::Sequence(expressions)
+                }
+                // Right-associative: `a = b = c` is `a = (b = c)`
+                Precedence::Assignment => {
+                    // Only `=` can destructure.
+                    let expression = unparenthesized(expression);
+                    let target = match (&operator.ttype, &expression) {
+                        (TokenType::Equal, _)
+                        | (_, Expression::Variable { .. } | Expression::Member { .. }) => {
+                            assignment_target(expression, &operator)?
+                        }
+                        _ => {
+                            return Err(JustError::syntax(
+                                "Invalid left-hand side in assignment",
+                                &operator,
+                            ))
+                        }
+                    };
+                    // Any `{ a = 1 }` on the left was a pattern all along.
+                    self.cover_initializer = None;
+                    let value = Box::new(self.parse_precedence(Precedence::Assignment)?);
+
+                    Expression::Assignment {
+                        target: Box::new(target),
+                        operator: operator.ttype.clone(),
+                        value,
+                        span: start,
+                    }
+                }
+                // Right-associative: `a ? b : c ? d : e` is
+                // `a ? b : (c ? d : e)`
+                Precedence::Conditional => {
+                    let then_branch = self.parse_precedence(Precedence::Assignment)?;
+                    self.advance();
+                    self.check(TokenType::Colon, ":")?;
+                    self.advance();
+                    let else_branch = self.parse_precedence(Precedence::Assignment)?;
+
+                    Expression::Conditional {
+                        condition: Box::new(expression),
+                        then_branch: Box::new(then_branch),
+                        else_branch: Box::new(else_branch),
+                    }
+                }
+                // `??` cannot be mixed with `&&` or `||` without parentheses,
+                // so its right operand stops before them, and either is an
+                // error when it meets the other as its left operand.
+                Precedence::Coalesce | Precedence::LogicalOr | Precedence::LogicalAnd => {
+                    let coalesces = operator.ttype == TokenType::QuestionQuestion;
+                    if let Expression::Logical {
+                        operator: left_operator,
+                        ..
+                    } = &expression
+                    {
+                        if coalesces != (*left_operator == TokenType::QuestionQuestion) {
+                            return Err(JustError::syntax(
+                                format!("Unexpected token '{}'", operator.lexeme),
+                                &operator,
+                            ));
+                        }
+                    }
+
+                    let right = if coalesces {
+                        self.parse_precedence(Precedence::BitwiseOr)?
+                    } else {
+                        self.parse_precedence(precedence.next())?
+                    };
+
+                    Expression::Logical {
+                        left: Box::new(expression),
+                        operator: operator.ttype.clone(),
+                        right: Box::new(right),
+                    }
+                }
+                // Right-associative, and an unparenthesized unary operand on
+                // the left is ambiguous: `-2 ** 2` is a syntax error.
+                Precedence::Exponent => {
+                    if let Expression::Unary { .. } = expression {
+                        return Err(JustError::syntax(
+                            "Unary operator used immediately before exponentiation expression. \
+                             Parenthesis must be used to disambiguate operator precedence",
+                            &operator,
+                        ));
+                    }
+
+                    Expression::Binary {
+                        left: Box::new(expression),
+                        operator: operator.ttype.clone(),
+                        right: Box::new(self.parse_precedence(Precedence::Exponent)?),
+                        span: Span::from(&operator),
+                    }
+                }
+                _ => Expression::Binary {
+                    left: Box::new(expression),
+                    operator: operator.ttype.clone(),
+                    right: Box::new(self.parse_precedence(precedence.next())?),
+                    span: Span::from(&operator),
+                },
+            };
+        }
+
+        self.cover_initializer = outer.or(self.cover_initializer.take());
+        Ok(expression)
+    }
+
+    fn unary(&mut self) -> Result<Expression, JustError> {
+        let token = self.peek();
+
+        match &token.ttype {
+            TokenType::Minus => {
                 self.advance();
                 Ok(Expression::Unary {
                     operator: "-".to_string(),
-                    right: Box::new(self.expression()?),
+                    right: Box::new(self.parse_precedence(Precedence::Unary)?),
+                })
+            }
+            TokenType::Plus => {
+                self.advance();
+                Ok(Expression::Unary {
+                    operator: "+".to_string(),
+                    right: Box::new(self.parse_precedence(Precedence::Unary)?),
                 })
             }
             TokenType::Bang => {
                 self.advance();
                 Ok(Expression::Unary {
                     operator: '!'.to_string(),
-                    right: Box::new(self.expression()?),
+                    right: Box::new(self.parse_precedence(Precedence::Unary)?),
+                })
+            }
+            TokenType::Void => {
+                self.advance();
+                Ok(Expression::Unary {
+                    operator: "void".to_string(),
+                    right: Box::new(self.parse_precedence(Precedence::Unary)?),
                 })
             }
-            _ => self.primary(),
+            TokenType::Tilde => {
+                self.advance();
+                Ok(Expression::Unary {
+                    operator: "~".to_string(),
+                    right: Box::new(self.parse_precedence(Precedence::Unary)?),
+                })
+            }
+            TokenType::Typeof => {
+                self.advance();
+                Ok(Expression::Unary {
+                    operator: "typeof".to_string(),
+                    right: Box::new(self.parse_precedence(Precedence::Unary)?),
+                })
+            }
+            TokenType::Delete => {
+                let operator = token.clone();
+                self.advance();
+                let right = self.parse_precedence(Precedence::Unary)?;
+                let mut target = &right;
+                while let Expression::Grouping(inner) = target {
+                    target = inner;
+                }
+                if matches!(target, Expression::Member { property, .. }
+                    if matches!(**property, Expression::PrivateName(_)))
+                {
+                    return Err(JustError::syntax(
+                        "Private fields can not be deleted",
+                        &operator,
+                    ));
+                }
+
+                Ok(Expression::Unary {
+                    operator: "delete".to_string(),
+                    right: Box::new(right),
+                })
+            }
+            TokenType::PlusPlus | TokenType::MinusMinus => {
+                let operator = token.clone();
+                self.advance();
+                let target = self.parse_precedence(Precedence::Unary)?;
+                update(target, operator, true)
+            }
+            _ => self.member(true),
+        }
+    }
+
+    /// Parses a primary or `new` expression followed by any number of
+    /// `.name` and `[expression]` property accesses, and `(arguments)` calls
+    /// when `calls` is set.
+    fn member(&mut self, calls: bool) -> Result<Expression, JustError> {
+        let start = Span::from(self.peek());
+        let mut expression = match self.peek().ttype {
+            TokenType::New => self.new_expression()?,
+            _ => self.primary()?,
         };
 
-        result
+        loop {
+            match self.peek_next().ttype {
+                TokenType::Dot => {
+                    self.advance();
+                    self.advance();
+                    let token = self.peek();
+                    let property = match (&token.ttype, property_name(token)) {
+                        (TokenType::PrivateName(name), _) => {
+                            Expression::PrivateName(name.to_string())
+                        }
+                        (_, Some(name)) => Expression::Literal(Literal::String(name)),
+                        (_, None) => {
+                            let message = format!("Unexpected token {:?}", token.lexeme);
+                            return Err(JustError::syntax(message, token));
+                        }
+                    };
+
+                    expression = Expression::Member {
+                        object: Box::new(expression),
+                        property: Box::new(property),
+                        computed: false,
+                        span: Span::from(token),
+                    };
+                }
+                TokenType::LBracket => {
+                    self.advance();
+                    let bracket = Span::from(self.peek());
+                    self.advance();
+                    let property = self.expression()?;
+                    self.advance();
+                    self.check(TokenType::RBracket, "]")?;
+
+                    expression = Expression::Member {
+                        object: Box::new(expression),
+                        property: Box::new(property),
+                        computed: true,
+                        span: bracket.to(self.peek().into()),
+                    };
+                }
+                TokenType::LParen if calls => {
+                    self.advance();
+                    let arguments = self.arguments()?;
+
+                    expression = Expression::Call {
+                        callee: Box::new(expression),
+                        arguments,
+                        span: start.to(self.peek().into()),
+                    };
+                }
+                _ => break,
+            }
+        }
+
+        Ok(expression)
+    }
+
+    /// Parses `new F(arguments)`. The callee may contain property accesses
+    /// but no calls, so `new a.B().c()` constructs `a.B` and then calls `c`
+    /// on the result.
+    fn new_expression(&mut self) -> Result<Expression, JustError> {
+        let start = Span::from(self.peek());
+        self.advance();
+        let callee = self.member(false)?;
+
+        let arguments = if self.peek_next().ttype == TokenType::LParen {
+            self.advance();
+            self.arguments()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Expression::New {
+            callee: Box::new(callee),
+            arguments,
+            span: start.to(self.peek().into()),
+        })
+    }
+
+    /// Parses `super(arguments)`, `super.name` or `super[expression]`,
+    /// starting on `super`.
+    fn super_expression(&mut self) -> Result<Expression, JustError> {
+        let start = Span::from(self.peek());
+
+        match self.peek_next().ttype {
+            TokenType::LParen => {
+                self.advance();
+                let arguments = self.arguments()?;
+
+                Ok(Expression::SuperCall {
+                    arguments,
+                    span: start.to(self.peek().into()),
+                })
+            }
+            TokenType::Dot => {
+                self.advance();
+                self.advance();
+                let token = self.peek();
+                let name = match property_name(token) {
+                    Some(name) => name,
+                    None => {
+                        let message = format!("Unexpected token {:?}", token.lexeme);
+                        return Err(JustError::syntax(message, token));
+                    }
+                };
+
+                Ok(Expression::SuperMember {
+                    property: Box::new(Expression::Literal(Literal::String(name))),
+                    computed: false,
+                    span: Span::from(token),
+                })
+            }
+            TokenType::LBracket => {
+                self.advance();
+                let bracket = Span::from(self.peek());
+                self.advance();
+                let property = self.expression()?;
+                self.advance();
+                self.check(TokenType::RBracket, "]")?;
+
+                Ok(Expression::SuperMember {
+                    property: Box::new(property),
+                    computed: true,
+                    span: bracket.to(self.peek().into()),
+                })
+            }
+            _ => Err(JustError::syntax(
+                "'super' keyword unexpected here",
+                self.peek(),
+            )),
+        }
+    }
+
+    /// Parses a call's argument list, starting on the `(` and leaving the
+    /// parser on the closing `)`.
+    fn arguments(&mut self) -> Result<Vec<Expression>, JustError> {
+        let mut arguments = Vec::new();
+
+        loop {
+            self.advance();
+            if self.peek().ttype == TokenType::RParen {
+                break;
+            } else if self.is_at_end() {
+                return Err(JustError::syntax(
+                    "You must close function call with ')'",
+                    self.peek(),
+                ));
+            }
+
+            arguments.push(self.element()?);
+
+            self.advance();
+            match self.peek().ttype {
+                TokenType::Comma => {}
+                TokenType::RParen => break,
+                _ => self.check(TokenType::RParen, ")")?,
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    /// Parses an argument or array element, which may be spread: `...xs`.
+    /// An array literal may still become a destructuring pattern, so its
+    /// elements leave any shorthand initializer for the caller to judge.
+    fn element(&mut self) -> Result<Expression, JustError> {
+        if self.peek().ttype == TokenType::Ellipsis {
+            let start = Span::from(self.peek());
+            self.advance();
+            let argument = self.parse_precedence(Precedence::Assignment)?;
+            return Ok(Expression::Spread {
+                argument: Box::new(argument),
+                span: start.to(self.peek().into()),
+            });
+        }
+
+        self.parse_precedence(Precedence::Assignment)
     }
 
-    fn primary(&mut self) -> Result<Expression, String> {
+    /// Parses `[1, , 3]`, starting on the `[` and leaving the parser on the
+    /// closing `]`. A trailing comma does not add a hole.
+    fn array_literal(&mut self) -> Result<Expression, JustError> {
+        let start = Span::from(self.peek());
+        let mut elements = Vec::new();
+
+        loop {
+            self.advance();
+            match self.peek().ttype {
+                TokenType::RBracket => break,
+                TokenType::Comma => {
+                    elements.push(None);
+                    continue;
+                }
+                _ => {}
+            }
+
+            elements.push(Some(self.element()?));
+
+            self.advance();
+            match self.peek().ttype {
+                TokenType::Comma => {}
+                TokenType::RBracket => break,
+                _ => self.check(TokenType::RBracket, "]")?,
+            }
+        }
+
+        Ok(Expression::ArrayLiteral {
+            elements,
+            span: start.to(self.peek().into()),
+        })
+    }
+
+    /// Parses `{ a: 1, "b": 2, 3: c, d, ...e }`, starting on the `{` and leaving
+    /// the parser on the closing `}`.
+    fn object_literal(&mut self) -> Result<Expression, JustError> {
+        let start = Span::from(self.peek());
+        let mut properties = Vec::new();
+
+        loop {
+            self.advance();
+            let token = self.peek().clone();
+            let key = match &token.ttype {
+                TokenType::RBrace => break,
+                TokenType::Ellipsis => {
+                    self.advance();
+                    let source = self.parse_precedence(Precedence::Assignment)?;
+                    properties.push(PropertyDefinition::Spread(source));
+
+                    self.advance();
+                    if self.peek().ttype == TokenType::Comma {
+                        continue;
+                    }
+                    self.check(TokenType::RBrace, "}")?;
+                    break;
+                }
+                TokenType::String(key) => key.to_string(),
+                TokenType::Number(n) => number_to_string(*n),
+                _ => match property_name(&token) {
+                    Some(key) => key,
+                    None => {
+                        let message = format!("Unexpected token {:?}", token.lexeme);
+                        return Err(JustError::syntax(message, &token));
+                    }
+                },
+            };
+
+            let value = match (&token.ttype, &self.peek_next().ttype) {
+                (_, TokenType::Colon) => {
+                    self.advance();
+                    self.advance();
+                    self.parse_precedence(Precedence::Assignment)?
+                }
+                // `{ a = 1 }` is only allowed as a destructuring target
+                (TokenType::Identifier(name), TokenType::Equal) => {
+                    self.advance();
+                    let equal = self.peek().clone();
+                    self.advance();
+                    let default = self.parse_precedence(Precedence::Assignment)?;
+                    self.cover_initializer.get_or_insert(equal);
+
+                    Expression::Assignment {
+                        target: Box::new(Pattern::Identifier(name.to_string())),
+                        operator: TokenType::Equal,
+                        value: Box::new(default),
+                        span: Span::from(&token),
+                    }
+                }
+                // Shorthand `{ a }` for `{ a: a }`
+                (TokenType::Identifier(name), _) => Expression::Variable {
+                    name: name.to_string(),
+                    span: Span::from(&token),
+                },
+                _ => {
+                    let token = self.peek_next();
+                    let message = format!("Unexpected token, expected :, but got {}", token.lexeme);
+                    return Err(JustError::syntax(message, token));
+                }
+            };
+            properties.push(PropertyDefinition::Property(key, value));
+
+            self.advance();
+            match self.peek().ttype {
+                TokenType::Comma => {}
+                TokenType::RBrace => break,
+                _ => self.check(TokenType::RBrace, "}")?,
+            }
+        }
+
+        Ok(Expression::ObjectLiteral {
+            properties,
+            span: start.to(self.peek().into()),
+        })
+    }
+
+    fn primary(&mut self) -> Result<Expression, JustError> {
         let token = self.peek().clone();
 
         let result = match &token.ttype {
@@ -299,6 +1657,16 @@ impl Parser {
             TokenType::String(v) => Ok(Expression::Literal(Literal::String(v.clone()))),
             TokenType::Null => Ok(Expression::Literal(Literal::Null)),
             TokenType::Boolean(v) => Ok(Expression::Literal(Literal::Boolean(*v))),
+            TokenType::This => Ok(Expression::This(Span::from(&token))),
+            TokenType::Super => self.super_expression(),
+            TokenType::Class => Ok(Expression::Class(Box::new(self.class(false)?))),
+            TokenType::LBracket => self.array_literal(),
+            TokenType::LBrace => self.object_literal(),
+            TokenType::LParen if self.at_arrow_parameters() => {
+                let parameters = self.parameters()?;
+                self.advance();
+                self.arrow_function(parameters)
+            }
             TokenType::LParen => {
                 self.advance();
                 let expression = self.expression()?;
@@ -308,62 +1676,102 @@ impl Parser {
                     Ok(Expression::Grouping(Box::new(expression)))
                 } else {
                     let token = self.peek_next();
-                    let err = format!(
-                        "Unexpected token, Expected ')' but get {}, at {}:{}",
-                        token.lexeme, token.line, token.col
-                    );
-                    Err(err)
+                    let message =
+                        format!("Unexpected token, expected ')' but got {}", token.lexeme);
+                    Err(JustError::syntax(message, token))
                 }
             }
-            TokenType::Identifier(v) => match self.peek_next().ttype {
-                TokenType::Equal => {
-                    self.advance();
-                    self.advance();
+            TokenType::Identifier(v) if self.peek_next().ttype == TokenType::Arrow => {
+                let parameters = vec![Parameter {
+                    pattern: Pattern::Identifier(v.to_string()),
+                    default: None,
+                    rest: false,
+                }];
+                self.advance();
+                self.arrow_function(parameters)
+            }
+            TokenType::Identifier(v) => Ok(Expression::Variable {
+                name: v.to_string(),
+                span: Span::from(&token),
+            }),
+            TokenType::Function => self.function_expression(),
+            _ => {
+                let message = format!("Unexpected token {:?}", token.lexeme);
+                Err(JustError::syntax(message, &token))
+            }
+        };
 
-                    Ok(Expression::Assignment {
-                        name: v.to_string(),
-                        value: Box::new(self.expression()?),
-                    })
-                }
-                TokenType::LParen => {
-                    self.advance();
-                    let mut args = Vec::new();
+        result
+    }
 
-                    while self.peek_next().ttype != TokenType::RParen {
-                        self.advance();
-                        let current_token = self.peek();
-                        if current_token.ttype == TokenType::Comma {
-                            self.advance();
-                        } else if self.is_at_end() {
-                            let err = format!(
-                                "You must close function call with ')' at {}:{}",
-                                current_token.line, current_token.col
-                            );
-                            return Err(err);
-                        }
+    /// Runs `parse` in a new innermost scope holding `names`.
+    fn with_scope<T>(
+        &mut self,
+        names: HashMap<String, bool>,
+        parse: impl FnOnce(&mut Self) -> Result<T, JustError>,
+    ) -> Result<T, JustError> {
+        self.scopes.push(names);
+        let result = parse(self);
+        self.scopes.pop();
 
-                        args.push(self.expression()?);
-                    }
+        result
+    }
 
-                    self.advance();
+    /// Runs `parse` with `jumps` as the statements a jump can target, as in
+    /// a function body, which no jump can leave.
+    fn with_jumps<T>(
+        &mut self,
+        jumps: JumpTargets,
+        parse: impl FnOnce(&mut Self) -> Result<T, JustError>,
+    ) -> Result<T, JustError> {
+        let outer = std::mem::replace(&mut self.jumps, jumps);
+        let result = parse(self);
+        self.jumps = outer;
+
+        result
+    }
 
-                    Ok(Expression::Call {
-                        callee: Box::new(Expression::Variable(v.to_string())),
-                        arguments: args,
-                    })
+    /// Records the names a declaration starting at token `start` binds in
+    /// the innermost scope. A `let`, `const` or `class` may not share its
+    /// name with any other declaration there.
+    fn declare(
+        &mut self,
+        names: Vec<String>,
+        lexical: bool,
+        start: usize,
+    ) -> Result<(), JustError> {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("the program scope is never left");
+
+        for name in names {
+            match scope.get(&name) {
+                Some(&declared) if declared || lexical => {
+                    let token = self.tokens[start..=self.current]
+                        .iter()
+                        .rfind(
+                            |token| matches!(&token.ttype, TokenType::Identifier(n) if *n == name),
+                        )
+                        .unwrap_or(&self.tokens[start]);
+                    let message = format!("Identifier '{}' has already been declared", name);
+                    return Err(JustError::syntax(message, token));
+                }
+                _ => {
+                    scope.insert(name, lexical);
                 }
-                _ => Ok(Expression::Variable(v.to_string())),
-            },
-            _ => {
-                let err = format!(
-                    "Unexpected token {:?}, at {}:{}",
-                    token.lexeme, token.line, token.col
-                );
-                Err(err)
             }
-        };
+        }
 
-        result
+        Ok(())
+    }
+
+    /// Fails when the source nests too deeply to parse any further,
+    /// pointing at the token where it happened.
+    fn check_stack(&self) -> Result<(), JustError> {
+        self.stack
+            .check()
+            .map_err(|err| err.with_span(self.peek().into()))
     }
 
     fn is_at_end(&self) -> bool {
@@ -382,24 +1790,445 @@ impl Parser {
         &self.tokens[self.current + 1]
     }
 
+    fn peek_at(&self, offset: usize) -> &Token {
+        let index = (self.current + offset).min(self.tokens.len() - 1);
+        &self.tokens[index]
+    }
+
     fn advance(&mut self) {
         if !self.is_at_end() {
             self.current += 1;
         }
     }
 
-    fn check(&self, expected: TokenType, expected_lexeme: &str) -> Result<(), String> {
+    fn check(&self, expected: TokenType, expected_lexeme: &str) -> Result<(), JustError> {
         let token = self.peek();
 
         if token.ttype != expected {
-            let err_message = format!(
-                "Unexpected token, expected {}, but get {} at {}:{}",
-                expected_lexeme, token.lexeme, token.line, token.col
+            let message = format!(
+                "Unexpected token, expected {}, but got {}",
+                expected_lexeme, token.lexeme
             );
 
-            return Err(err_message);
+            return Err(JustError::syntax(message, token));
         }
 
         Ok(())
     }
 }
+
+/// The property name `token` spells after a `.` or as an object literal key.
+/// Keywords are allowed there, e.g. `obj.if` or `{ for: 1 }`.
+fn property_name(token: &Token) -> Option<String> {
+    match &token.ttype {
+        TokenType::Identifier(name) => Some(name.to_string()),
+        _ if token.lexeme.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+            Some(token.lexeme.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Reinterprets the left-hand side of `=` as the pattern it assigns to.
+fn assignment_target(expression: Expression, operator: &Token) -> Result<Pattern, JustError> {
+    let destructures = matches!(
+        expression,
+        Expression::ArrayLiteral { .. } | Expression::ObjectLiteral { .. }
+    );
+
+    match destructuring_target(expression) {
+        Some(target) => Ok(target),
+        None if destructures => Err(JustError::syntax(
+            "Invalid destructuring assignment target",
+            operator,
+        )),
+        None => Err(JustError::syntax(
+            "Invalid left-hand side in assignment",
+            operator,
+        )),
+    }
+}
+
+/// Builds `++target`, `target--` and the like. Only a variable or a member
+/// can be incremented.
+fn update(target: Expression, operator: Token, prefix: bool) -> Result<Expression, JustError> {
+    let target = unparenthesized(target);
+    let span = match &target {
+        Expression::Variable { span, .. } | Expression::Member { span, .. } => *span,
+        _ => {
+            let message = if prefix {
+                "Invalid left-hand side expression in prefix operation"
+            } else {
+                "Invalid left-hand side expression in postfix operation"
+            };
+            return Err(JustError::syntax(message, &operator));
+        }
+    };
+
+    Ok(Expression::Update {
+        target: Box::new(destructuring_target(target).expect("a variable or member")),
+        operator: operator.ttype,
+        prefix,
+        span,
+    })
+}
+
+/// Strips the parentheses from around a variable or member, which can be
+/// assigned to parenthesized, e.g. `(a) = 1` or `(o.x)++`. A parenthesized
+/// array or object literal cannot destructure, so it is left as it is.
+fn unparenthesized(expression: Expression) -> Expression {
+    match expression {
+        Expression::Grouping(inner) => match unparenthesized(*inner) {
+            target @ (Expression::Variable { .. } | Expression::Member { .. }) => target,
+            inner => Expression::Grouping(Box::new(inner)),
+        },
+        expression => expression,
+    }
+}
+
+/// Reads the left-hand side of an assignment as a pattern: a variable, a
+/// member, or an array or object literal that destructures, e.g.
+/// `[a, b.c = 1, ...d]` or `{ a, b: [c] }`.
+fn destructuring_target(expression: Expression) -> Option<Pattern> {
+    let expression = unparenthesized(expression);
+    match expression {
+        Expression::Variable { name, .. } => Some(Pattern::Identifier(name)),
+        Expression::Member { .. } => Some(Pattern::Member(expression)),
+        Expression::ArrayLiteral { elements, span } => {
+            let mut elements = elements.into_iter().peekable();
+            let mut patterns = Vec::new();
+            let mut rest = None;
+
+            while let Some(element) = elements.next() {
+                match element {
+                    Some(Expression::Spread { argument, .. }) if elements.peek().is_none() => {
+                        rest = Some(Box::new(destructuring_target(*argument)?));
+                    }
+                    Some(element) => patterns.push(Some(pattern_element(element)?)),
+                    None => patterns.push(None),
+                }
+            }
+
+            Some(Pattern::Array {
+                elements: patterns,
+                rest,
+                span,
+            })
+        }
+        Expression::ObjectLiteral {
+            properties: definitions,
+            span,
+        } => {
+            let mut definitions = definitions.into_iter().peekable();
+            let mut properties = Vec::new();
+            let mut rest = None;
+
+            while let Some(definition) = definitions.next() {
+                match definition {
+                    PropertyDefinition::Property(key, value) => properties.push(PatternProperty {
+                        key: Expression::Literal(Literal::String(key)),
+                        computed: false,
+                        value: pattern_element(value)?,
+                    }),
+                    PropertyDefinition::Spread(target) if definitions.peek().is_none() => {
+                        match destructuring_target(target)? {
+                            target @ (Pattern::Identifier(_) | Pattern::Member(_)) => {
+                                rest = Some(Box::new(target))
+                            }
+                            _ => return None,
+                        }
+                    }
+                    PropertyDefinition::Spread(_) => return None,
+                }
+            }
+
+            Some(Pattern::Object {
+                properties,
+                rest,
+                span,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Reads an element of a destructuring assignment, where `target = value`
+/// gives the target a default.
+fn pattern_element(expression: Expression) -> Option<PatternElement> {
+    match expression {
+        Expression::Assignment {
+            target,
+            operator: TokenType::Equal,
+            value,
+            ..
+        } => Some(PatternElement {
+            target: *target,
+            default: Some(*value),
+        }),
+        expression => Some(PatternElement {
+            target: destructuring_target(expression)?,
+            default: None,
+        }),
+    }
+}
+
+/// Whether a statement can only begin at `ttype`, which makes it a safe
+/// place to resume parsing after a syntax error.
+fn starts_statement(ttype: &TokenType) -> bool {
+    matches!(
+        ttype,
+        TokenType::Let
+            | TokenType::Const
+            | TokenType::Var
+            | TokenType::Function
+            | TokenType::Class
+            | TokenType::If
+            | TokenType::For
+            | TokenType::While
+            | TokenType::Break
+            | TokenType::Continue
+            | TokenType::Throw
+            | TokenType::Return
+    )
+}
+
+/// Operator precedence levels from ECMAScript, loosest first.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Precedence {
+    Sequence,       // ,
+    Assignment,     // = += -= ...
+    Conditional,    // ?:
+    Coalesce,       // ??
+    LogicalOr,      // ||
+    LogicalAnd,     // &&
+    BitwiseOr,      // |
+    BitwiseXor,     // ^
+    BitwiseAnd,     // &
+    Equality,       // == != === !==
+    Relational,     // < <= > >= in instanceof
+    Shift,          // << >> >>>
+    Additive,       // + -
+    Multiplicative, // * / %
+    Exponent,       // **
+    Unary,          // ! - + ~ typeof void delete
+    Postfix,        // x++ x--
+    Call,           // f() a.b a[b] new
+}
+
+impl Precedence {
+    /// The next tighter level, used as the minimum precedence of the right
+    /// operand of a left-associative operator.
+    fn next(self) -> Precedence {
+        match self {
+            Precedence::Sequence => Precedence::Assignment,
+            Precedence::Assignment => Precedence::Conditional,
+            Precedence::Conditional => Precedence::Coalesce,
+            Precedence::Coalesce => Precedence::LogicalOr,
+            Precedence::LogicalOr => Precedence::LogicalAnd,
+            Precedence::LogicalAnd => Precedence::BitwiseOr,
+            Precedence::BitwiseOr => Precedence::BitwiseXor,
+            Precedence::BitwiseXor => Precedence::BitwiseAnd,
+            Precedence::BitwiseAnd => Precedence::Equality,
+            Precedence::Equality => Precedence::Relational,
+            Precedence::Relational => Precedence::Shift,
+            Precedence::Shift => Precedence::Additive,
+            Precedence::Additive => Precedence::Multiplicative,
+            Precedence::Multiplicative => Precedence::Exponent,
+            Precedence::Exponent => Precedence::Unary,
+            Precedence::Unary => Precedence::Postfix,
+            Precedence::Postfix | Precedence::Call => Precedence::Call,
+        }
+    }
+}
+
+/// The precedence of `ttype` when it appears between two operands, or
+/// `None` if it is not an infix operator.
+fn infix_precedence(ttype: &TokenType) -> Option<Precedence> {
+    let precedence = match ttype {
+        TokenType::Equal
+        | TokenType::PlusEqual
+        | TokenType::MinusEqual
+        | TokenType::StarEqual
+        | TokenType::SlashEqual
+        | TokenType::PercentEqual
+        | TokenType::PowerEqual
+        | TokenType::LesserLesserEqual
+        | TokenType::GreaterGreaterEqual
+        | TokenType::GreaterGreaterGreaterEqual
+        | TokenType::AmpersandEqual
+        | TokenType::PipeEqual
+        | TokenType::CaretEqual
+        | TokenType::AndEqual
+        | TokenType::OrEqual
+        | TokenType::QuestionQuestionEqual => Precedence::Assignment,
+        TokenType::Comma => Precedence::Sequence,
+        TokenType::Question => Precedence::Conditional,
+        TokenType::QuestionQuestion => Precedence::Coalesce,
+        TokenType::Or => Precedence::LogicalOr,
+        TokenType::And => Precedence::LogicalAnd,
+        TokenType::Pipe => Precedence::BitwiseOr,
+        TokenType::Caret => Precedence::BitwiseXor,
+        TokenType::Ampersand => Precedence::BitwiseAnd,
+        TokenType::EqualEqual
+        | TokenType::BangEqual
+        | TokenType::EqualEqualEqual
+        | TokenType::BangEqualEqual => Precedence::Equality,
+        TokenType::Lesser
+        | TokenType::LesserEqual
+        | TokenType::Greater
+        | TokenType::GreaterEqual
+        | TokenType::In
+        | TokenType::Instanceof => Precedence::Relational,
+        TokenType::LesserLesser | TokenType::GreaterGreater | TokenType::GreaterGreaterGreater => {
+            Precedence::Shift
+        }
+        TokenType::Plus | TokenType::Minus => Precedence::Additive,
+        TokenType::Star | TokenType::Slash | TokenType::Percent => Precedence::Multiplicative,
+        TokenType::Power => Precedence::Exponent,
+        _ => return None,
+    };
+
+    Some(precedence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lexer::Lexer,
+        testing::{assert_number, error, evaluate},
+    };
+
+    fn parse(source: &str) -> (Vec<Statement>, Vec<JustError>) {
+        let tokens = Lexer::new(source.to_string())
+            .tokenize()
+            .expect("the source lexes");
+        Parser::new(tokens).parse()
+    }
+
+    #[test]
+    fn binary_operators_follow_precedence_and_associativity() {
+        let cases = [
+            ("2 * 3 + 4", 10.0),
+            ("2 + 3 * 4", 14.0),
+            ("10 - 2 - 3", 5.0),
+            ("100 / 10 / 5", 2.0),
+            ("2 * 3 % 4", 2.0),
+            ("7 % 4 * 2", 6.0),
+            ("1 - 2 + 3", 2.0),
+            ("2 ** 3 ** 2", 512.0),
+            ("(2 ** 3) ** 2", 64.0),
+            ("2 * 3 ** 2", 18.0),
+            ("(-2) ** 2", 4.0),
+            ("-2 * -3", 6.0),
+            ("2 - -2", 4.0),
+            ("(1 + 2) * (3 + 4)", 21.0),
+            ("1 + 2 < 4 === true ? 10 : 20", 10.0),
+            ("let a = 1; let b = 2; a = b = 3; a + b", 6.0),
+            ("let c = 2; c **= 3 ** 0; c", 2.0),
+            ("let d = 1; d = 2, d += 3; d", 5.0),
+            ("(1, 2) * 3", 6.0),
+            ("let e = (1, 2), f = 3; e + f", 5.0),
+            ("[1, (2, 3)][1]", 3.0),
+            ("let g = [1]; (0 in g) + 1", 2.0),
+        ];
+
+        for (source, expected) in cases {
+            assert_number(source, expected);
+        }
+    }
+
+    #[test]
+    fn unary_minus_before_exponent_is_ambiguous() {
+        for source in ["-2 ** 2", "let x = 2; -x ** 2"] {
+            let (_, errors) = parse(source);
+            assert!(
+                errors.iter().any(|error| error
+                    .message()
+                    .starts_with("Unary operator used immediately before exponentiation")),
+                "{} is not rejected: {:?}",
+                source,
+                errors
+            );
+        }
+    }
+
+    #[test]
+    fn deep_nesting_is_a_range_error() {
+        let depth = 10000;
+        let sources = [
+            format!("{}1{}", "(".repeat(depth), ")".repeat(depth)),
+            format!("{}{}", "[".repeat(depth), "]".repeat(depth)),
+            format!("{}{}", "{".repeat(depth), "}".repeat(depth)),
+            format!("{}1", "-".repeat(depth)),
+            format!("let {}a{} = 1", "[".repeat(depth), "]".repeat(depth)),
+        ];
+
+        for source in &sources {
+            let error = error(source);
+            assert_eq!(error.name(), "RangeError", "{:.20}...", source);
+            assert_eq!(error.message(), "Maximum call stack size exceeded");
+            assert!(error.span().is_some(), "{:.20}... has no span", source);
+        }
+    }
+
+    #[test]
+    fn jumps_without_a_target_are_early_errors() {
+        let cases = [
+            ("1;\nbreak;", "Illegal break statement", "break"),
+            ("a: { break; }", "Illegal break statement", "break"),
+            (
+                "while (true) { function f() { break; } }",
+                "Illegal break statement",
+                "break",
+            ),
+            (
+                "continue;",
+                "Illegal continue statement: no surrounding iteration statement",
+                "continue",
+            ),
+            ("while (true) { break a; }", "Undefined label 'a'", "a"),
+            (
+                "a: while (true) { () => { break a; }; }",
+                "Undefined label 'a'",
+                "a",
+            ),
+            (
+                "a: { while (true) { continue a; } }",
+                "Illegal continue statement: 'a' does not denote an iteration statement",
+                "a",
+            ),
+            ("a: a: ;", "Label 'a' has already been declared", "a"),
+            ("return 1;", "Illegal return statement", "return"),
+            (
+                "class A { static { return; } }",
+                "Illegal return statement",
+                "return",
+            ),
+        ];
+
+        for (source, message, lexeme) in cases {
+            // Early errors stop the script before any of it runs.
+            let text = format!("let x = 1;\n{}", source);
+            let error = error(&text);
+            assert_eq!(error.name(), "SyntaxError", "{}", source);
+            assert_eq!(error.message(), message, "{}", source);
+            let span = error.span().expect("early errors have a span");
+            assert_eq!(&text[span.start_pos..span.end_pos], lexeme, "{}", source);
+        }
+    }
+
+    #[test]
+    fn jumps_to_enclosing_statements_parse() {
+        let sources = [
+            "a: { break a; }",
+            "let j = 0; a: b: while (j < 3) { j++; continue a; }",
+            "let i = 0; a: while (i < 3) { i++; for (;;) { continue a; } } i",
+            "function f() { while (true) { return 1; } } f()",
+        ];
+
+        for source in sources {
+            evaluate(&format!("{};\n0", source));
+        }
+    }
+}
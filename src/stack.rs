@@ -0,0 +1,57 @@
+//! Guards against overflowing the native stack. The parser and the
+//! interpreter recurse on the Rust stack for every nested expression,
+//! statement and call, so a script nesting them deeply enough would
+//! otherwise abort the whole process rather than fail with a `RangeError`.
+
+use crate::error::JustError;
+
+/// The stack assumed to be available when none is given: what a thread
+/// spawned by the standard library gets by default.
+pub const DEFAULT_STACK_SIZE: usize = 2 * 1024 * 1024;
+
+/// How much of the stack is kept free: enough for the frames between two
+/// checks, for unwinding with the error and for dropping what was built.
+const RESERVE: usize = 256 * 1024;
+
+/// Measures how much stack has been used since a starting point, and
+/// fails once that comes close to the stack's size.
+#[derive(Debug, Clone, Copy)]
+pub struct StackGuard {
+    size: usize,
+    base: usize,
+}
+
+impl StackGuard {
+    /// A guard for a thread with `size` bytes of stack, measuring from the
+    /// current position.
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            base: position(),
+        }
+    }
+
+    /// Measures from the current position from now on. The parser and
+    /// interpreter call this when they start, as close to the top of the
+    /// stack as they get.
+    pub fn reset(&mut self) {
+        self.base = position();
+    }
+
+    /// Fails with the error JavaScript engines give for runaway recursion
+    /// once less than the reserve is left.
+    pub fn check(&self) -> Result<(), JustError> {
+        let used = self.base.abs_diff(position());
+        if used + RESERVE > self.size {
+            return Err(JustError::range("Maximum call stack size exceeded"));
+        }
+
+        Ok(())
+    }
+}
+
+/// The address of a local variable, which tracks how deep the stack is.
+fn position() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
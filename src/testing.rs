@@ -0,0 +1,70 @@
+//! Helpers shared by the unit tests, which mostly check what a script
+//! evaluates to.
+
+use crate::{environment::Value, error::JustError, stack::DEFAULT_STACK_SIZE};
+
+/// Runs `source` like the command line does, returning the value of its
+/// last expression. Tests run on threads with the default stack size.
+pub fn run(source: &str) -> Result<Option<Value>, Vec<JustError>> {
+    crate::run(source.to_string(), DEFAULT_STACK_SIZE)
+}
+
+/// The value of the last expression in `source`, which has to run without
+/// errors.
+pub fn evaluate(source: &str) -> Value {
+    match run(source) {
+        Ok(Some(value)) => value,
+        Ok(None) => panic!("{} does not end in an expression", source),
+        Err(errors) => panic!("{} fails: {:?}", source, errors),
+    }
+}
+
+/// The first error `source` fails with, whether it is found while parsing
+/// or while running.
+pub fn error(source: &str) -> JustError {
+    match run(source) {
+        Ok(value) => panic!("{} gives {:?} instead of failing", source, value),
+        Err(mut errors) => errors.remove(0),
+    }
+}
+
+/// Whether `a` and `b` are the same number, telling `0` from `-0` and
+/// taking NaN to equal itself.
+pub fn same_number(a: f64, b: f64) -> bool {
+    a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())
+}
+
+pub fn assert_number(source: &str, expected: f64) {
+    match evaluate(source) {
+        Value::Number(actual) => assert!(
+            same_number(actual, expected),
+            "{} is {}, not {}",
+            source,
+            actual,
+            expected
+        ),
+        value => panic!("{} is {}, not a number", source, value),
+    }
+}
+
+pub fn assert_bool(source: &str, expected: bool) {
+    match evaluate(source) {
+        Value::Boolean(actual) => assert_eq!(actual, expected, "{}", source),
+        value => panic!("{} is {}, not a boolean", source, value),
+    }
+}
+
+pub fn assert_string(source: &str, expected: &str) {
+    match evaluate(source) {
+        Value::String(actual) => assert_eq!(actual, expected, "{}", source),
+        value => panic!("{} is {}, not a string", source, value),
+    }
+}
+
+/// Checks that `source` fails with `expected`, spelled as the error's name
+/// and message, e.g. `TypeError: x is not a function`.
+pub fn assert_error(source: &str, expected: &str) {
+    let error = error(source);
+    let actual = format!("{}: {}", error.name(), error.message());
+    assert_eq!(actual, expected, "{}", source);
+}